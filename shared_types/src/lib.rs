@@ -1,6 +1,7 @@
 pub mod transcript;
 
-use halo2curves::ff::FromUniformBytes;
+use anyhow::{anyhow, Result};
+use halo2curves::ff::{FromUniformBytes, PrimeField};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -22,7 +23,10 @@ pub type Base = <Bn256Point as CurveExt>::Base;
 pub trait Field:
 ff_field
     + FromUniformBytes<64> // only need this bc of Poseidon transcript,
-                              // see func `next_field_element_without_rejection`
+                              // see func `next_field_element_without_rejection`,
+                              // which reduces 64 uniform bytes mod |F| rather
+                              // than rejection sampling; the result is only
+                              // statistically close to uniform (bias ~ |F| / 2^512).
     + Hash
     + Ord
     + Serialize
@@ -70,6 +74,26 @@ pub trait HasByteRepresentation {
     fn vec_from_bytes_le(bytes: &[u8]) -> Vec<Self>
     where
         Self: Sized;
+
+    /// Inverse of `vec_from_bytes_le`: concatenates the `to_bytes_le` of each
+    /// element, for a total of `elems.len() * REPR_NUM_BYTES` bytes.
+    fn vec_to_bytes_le(elems: &[Self]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        elems.iter().flat_map(Self::to_bytes_le).collect()
+    }
+
+    /// The standard little-endian encoding of the element as an integer in
+    /// `[0, p)`, of length `REPR_NUM_BYTES`, as expected by other proof systems
+    /// (e.g. Ethereum contracts).
+    fn to_canonical_bytes_le(&self) -> Vec<u8>;
+
+    /// Inverse of `to_canonical_bytes_le`. Returns an error unless `bytes` has
+    /// length `REPR_NUM_BYTES` and encodes an integer less than `p`.
+    fn from_canonical_bytes_le(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized;
 }
 
 impl HasByteRepresentation for Fr {
@@ -163,4 +187,186 @@ impl HasByteRepresentation for Fr {
             .map(Self::from_bytes_le)
             .collect()
     }
+
+    fn to_canonical_bytes_le(&self) -> Vec<u8> {
+        // `to_repr` converts out of Montgomery form.
+        self.to_repr().as_ref().to_vec()
+    }
+
+    fn from_canonical_bytes_le(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        if bytes.len() != repr.as_ref().len() {
+            return Err(anyhow!(
+                "Expected {} bytes, got {}",
+                Self::REPR_NUM_BYTES,
+                bytes.len()
+            ));
+        }
+        repr.as_mut().copy_from_slice(bytes);
+        Option::from(Fr::from_repr(repr)).ok_or(anyhow!(
+            "Bytes do not encode an integer less than the modulus"
+        ))
+    }
+}
+
+/// A vector of exactly `N` elements, represented by the concatenation of the
+/// elements' representations, for a total of `N * F::REPR_NUM_BYTES` bytes.
+/// Lets e.g. an MLE's bookkeeping table or a round message be serialised as a
+/// single blob of known length.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldElementVec<F, const N: usize>(Vec<F>);
+
+impl<F: HasByteRepresentation, const N: usize> FieldElementVec<F, N> {
+    /// Panics unless `elems` has exactly `N` elements.
+    pub fn new(elems: Vec<F>) -> Self {
+        assert_eq!(elems.len(), N);
+        Self(elems)
+    }
+
+    pub fn into_inner(self) -> Vec<F> {
+        self.0
+    }
+}
+
+impl<F: HasByteRepresentation, const N: usize> HasByteRepresentation for FieldElementVec<F, N> {
+    const REPR_NUM_BYTES: usize = N * F::REPR_NUM_BYTES;
+
+    /// As for a single element, a short `bytes` is padded with 0s at the end,
+    /// i.e. the trailing elements are zero-padded.
+    fn from_bytes_le(bytes: &[u8]) -> Self {
+        if bytes.len() > Self::REPR_NUM_BYTES {
+            panic!(
+                "Error: Attempted to convert from greater than {}-length byte vector",
+                Self::REPR_NUM_BYTES
+            )
+        }
+        let mut padded = bytes.to_vec();
+        padded.resize(Self::REPR_NUM_BYTES, 0);
+        Self(
+            padded
+                .chunks(F::REPR_NUM_BYTES)
+                .map(F::from_bytes_le)
+                .collect(),
+        )
+    }
+
+    fn to_bytes_le(&self) -> Vec<u8> {
+        F::vec_to_bytes_le(&self.0)
+    }
+
+    fn to_u64s_le(&self) -> Vec<u64> {
+        self.0.iter().flat_map(F::to_u64s_le).collect()
+    }
+
+    fn from_u64s_le(words: Vec<u64>) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(
+            words
+                .chunks(F::REPR_NUM_BYTES / 8)
+                .map(|chunk| F::from_u64s_le(chunk.to_vec()))
+                .collect(),
+        )
+    }
+
+    fn vec_from_bytes_le(bytes: &[u8]) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        bytes
+            .chunks(Self::REPR_NUM_BYTES)
+            .map(Self::from_bytes_le)
+            .collect()
+    }
+
+    fn to_canonical_bytes_le(&self) -> Vec<u8> {
+        self.0.iter().flat_map(F::to_canonical_bytes_le).collect()
+    }
+
+    fn from_canonical_bytes_le(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        if bytes.len() != Self::REPR_NUM_BYTES {
+            return Err(anyhow!(
+                "Expected {} bytes, got {}",
+                Self::REPR_NUM_BYTES,
+                bytes.len()
+            ));
+        }
+        bytes
+            .chunks(F::REPR_NUM_BYTES)
+            .map(F::from_canonical_bytes_le)
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ff_field, FieldElementVec, Fr, HasByteRepresentation};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_canonical_bytes() {
+        let mut one = vec![0_u8; 32];
+        one[0] = 1;
+        assert_eq!(Fr::ONE.to_canonical_bytes_le(), one);
+        assert_eq!(Fr::from_canonical_bytes_le(&one).unwrap(), Fr::ONE);
+
+        let elem = Fr::from(0x0102_0304_0506_0708);
+        let bytes = elem.to_canonical_bytes_le();
+        assert_eq!(bytes[..8], [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(Fr::from_canonical_bytes_le(&bytes).unwrap(), elem);
+
+        // p - 1 is the largest canonical encoding.
+        let minus_one = (-Fr::ONE).to_canonical_bytes_le();
+        assert_eq!(Fr::from_canonical_bytes_le(&minus_one).unwrap(), -Fr::ONE);
+        let mut modulus = minus_one;
+        modulus[0] += 1;
+        assert!(Fr::from_canonical_bytes_le(&modulus).is_err());
+        assert!(Fr::from_canonical_bytes_le(&[1]).is_err());
+    }
+
+    #[test]
+    fn test_vec_bytes_round_trip() {
+        let mut rng = test_rng();
+        for len in [0, 1, 2, 7, 64] {
+            let elems: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+            let bytes = Fr::vec_to_bytes_le(&elems);
+            assert_eq!(bytes.len(), len * Fr::REPR_NUM_BYTES);
+            assert_eq!(Fr::vec_from_bytes_le(&bytes), elems);
+        }
+    }
+
+    #[test]
+    fn test_field_element_vec_round_trip() {
+        let mut rng = test_rng();
+        let elems: Vec<Fr> = (0..4).map(|_| Fr::random(&mut rng)).collect();
+        let vec = FieldElementVec::<Fr, 4>::new(elems.clone());
+        assert_eq!(
+            FieldElementVec::<Fr, 4>::REPR_NUM_BYTES,
+            4 * Fr::REPR_NUM_BYTES
+        );
+
+        let bytes = vec.to_bytes_le();
+        assert_eq!(bytes, Fr::vec_to_bytes_le(&elems));
+        assert_eq!(FieldElementVec::from_bytes_le(&bytes), vec);
+        assert_eq!(FieldElementVec::from_u64s_le(vec.to_u64s_le()), vec);
+        let canonical = vec.to_canonical_bytes_le();
+        assert_eq!(
+            FieldElementVec::<Fr, 4>::from_canonical_bytes_le(&canonical).unwrap(),
+            vec
+        );
+        assert!(FieldElementVec::<Fr, 4>::from_canonical_bytes_le(&canonical[1..]).is_err());
+        assert_eq!(vec.into_inner(), elems);
+
+        // Trailing elements are zero-padded.
+        let short = FieldElementVec::<Fr, 4>::from_bytes_le(&bytes[..Fr::REPR_NUM_BYTES]);
+        assert_eq!(short.into_inner(), [elems[0], Fr::ZERO, Fr::ZERO, Fr::ZERO]);
+    }
 }
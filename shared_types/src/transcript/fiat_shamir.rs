@@ -0,0 +1,76 @@
+//! Enforces the message structure of a Fiat-Shamir transformed protocol on
+//! top of a `TranscriptSponge`.
+
+use super::TranscriptSponge;
+use crate::Field;
+use std::marker::PhantomData;
+
+/// Wraps a [TranscriptSponge] for the duration of a public-coin protocol,
+/// where every verifier challenge must be preceded by (at least) one prover
+/// message. Squeezing two challenges without absorbing a prover message in
+/// between panics, which catches provers that derive a challenge before they
+/// have absorbed the message it is supposed to depend on.
+#[derive(Debug)]
+pub struct FiatShamir<'a, T: TranscriptSponge<F>, F: Field> {
+    transcript: &'a mut T,
+    has_unused_prover_message: bool,
+    _marker: PhantomData<F>,
+}
+
+impl<'a, T: TranscriptSponge<F>, F: Field> FiatShamir<'a, T, F> {
+    pub fn new(transcript: &'a mut T) -> Self {
+        Self {
+            transcript,
+            has_unused_prover_message: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Absorbs a message sent by the prover.
+    pub fn absorb_prover_message(&mut self, msg: &[F]) {
+        self.transcript.absorb_elements(msg);
+        self.has_unused_prover_message = true;
+    }
+
+    /// Squeezes the verifier's challenge for the prover messages absorbed
+    /// since the last challenge. Panics if there are none.
+    pub fn get_verifier_challenge(&mut self) -> F {
+        assert!(
+            self.has_unused_prover_message,
+            "Error: Verifier challenge requested before absorbing a prover message"
+        );
+        self.has_unused_prover_message = false;
+        self.transcript.squeeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FiatShamir;
+    use crate::{
+        transcript::{poseidon_sponge::PoseidonSponge, TranscriptSponge},
+        Fr,
+    };
+
+    #[test]
+    fn test_matches_underlying_transcript() {
+        let mut plain = PoseidonSponge::<Fr>::default();
+        plain.absorb_elements(&[Fr::from(1), Fr::from(2)]);
+        let expected = plain.squeeze();
+
+        let mut transcript = PoseidonSponge::<Fr>::default();
+        let mut fiat_shamir = FiatShamir::new(&mut transcript);
+        fiat_shamir.absorb_prover_message(&[Fr::from(1), Fr::from(2)]);
+        assert_eq!(fiat_shamir.get_verifier_challenge(), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_two_challenges_without_prover_message() {
+        let mut transcript = PoseidonSponge::<Fr>::default();
+        let mut fiat_shamir = FiatShamir::new(&mut transcript);
+        fiat_shamir.absorb_prover_message(&[Fr::from(1)]);
+        fiat_shamir.get_verifier_challenge();
+        fiat_shamir.get_verifier_challenge();
+    }
+}
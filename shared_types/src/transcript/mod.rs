@@ -1,4 +1,8 @@
+pub mod fiat_shamir;
+pub mod labeled_transcript;
 pub mod poseidon_sponge;
+use crate::{Bn256Point, Field};
+use halo2curves::{ff::PrimeField, group::Curve};
 use std::fmt::Debug;
 
 /// A `TranscriptSponge` provides the basic interface for a cryptographic sponge
@@ -6,7 +10,11 @@ use std::fmt::Debug;
 /// transcript of an interactive protocol turned non-interactive view
 /// Fiat-Shamir.
 pub trait TranscriptSponge<F>: Clone + Send + Sync + Default + Debug {
-    /// Absorb the initialization label.
+    /// Forks the transcript on `label`: afterwards, the sponge behaves exactly
+    /// like a fresh transcript which has been seeded with a hash of `label`,
+    /// regardless of anything absorbed or squeezed before. Transcripts with the
+    /// same label thus produce the same challenges for the same subsequent
+    /// absorbs, and transcripts with different labels produce different ones.
     fn absorb_initialization_label(&mut self, label: &str);
 
     /// Absorb a single field element `elem`.
@@ -15,6 +23,21 @@ pub trait TranscriptSponge<F>: Clone + Send + Sync + Default + Debug {
     /// Absorb a list of field elements sequentially.
     fn absorb_elements(&mut self, elements: &[F]);
 
+    /// Absorb `label` followed by `elem`, so that sub-protocols absorbing the
+    /// same element at the same position of the transcript under different
+    /// labels produce different challenges. Unlike
+    /// [TranscriptSponge::absorb_initialization_label], this keeps everything
+    /// absorbed so far. By default, absorbs the [label_elements] of `label`
+    /// and `elem` in a single `absorb_elements`.
+    fn absorb_labeled(&mut self, label: &[u8], elem: F)
+    where
+        F: Field,
+    {
+        let mut elements = label_elements(label);
+        elements.push(elem);
+        self.absorb_elements(&elements);
+    }
+
     /// Generate a field element by squeezing the sponge. Internal state is
     /// modified.
     fn squeeze(&mut self) -> F;
@@ -23,3 +46,70 @@ pub trait TranscriptSponge<F>: Clone + Send + Sync + Default + Debug {
     /// `num_elements` times. Internal state is modified.
     fn squeeze_elements(&mut self, num_elements: usize) -> Vec<F>;
 }
+
+/// The encoding of `label` absorbed by [TranscriptSponge::absorb_labeled]: its
+/// length, followed by its bytes in chunks of `REPR_NUM_BYTES - 1`. Each chunk
+/// is less than the field modulus, so any label is a valid field element
+/// sequence.
+pub fn label_elements<F: Field>(label: &[u8]) -> Vec<F> {
+    std::iter::once(F::from(label.len() as u64))
+        .chain(label.chunks(F::REPR_NUM_BYTES - 1).map(F::from_bytes_le))
+        .collect()
+}
+
+/// Absorbs the affine coordinates x, y of `point`, e.g. a polynomial
+/// commitment sent to the verifier. The coordinates lie in the base field Fq,
+/// whose modulus exceeds that of the scalar field, so each coordinate's
+/// little-endian bytes are split into two 16-byte halves, each of which fits
+/// into an element of `F`.
+pub fn absorb_g1_point<F: Field, T: TranscriptSponge<F>>(transcript: &mut T, point: &Bn256Point) {
+    let affine = point.to_affine();
+    let elements: Vec<F> = [affine.x, affine.y]
+        .iter()
+        .flat_map(|coordinate| {
+            let bytes = coordinate.to_repr();
+            bytes
+                .as_ref()
+                .chunks(16)
+                .map(F::from_bytes_le)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    transcript.absorb_elements(&elements);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        absorb_g1_point, label_elements, poseidon_sponge::PoseidonSponge, TranscriptSponge,
+    };
+    use crate::{Bn256Point, Fr, Group};
+
+    #[test]
+    fn test_absorb_g1_point() {
+        let challenge = |point: &Bn256Point| {
+            let mut sponge = PoseidonSponge::<Fr>::default();
+            absorb_g1_point(&mut sponge, point);
+            sponge.squeeze()
+        };
+        let generator = Bn256Point::generator();
+        assert_ne!(challenge(&generator), challenge(&generator.double()));
+        assert_eq!(challenge(&generator), challenge(&generator));
+        challenge(&Bn256Point::identity());
+    }
+
+    #[test]
+    fn test_absorb_labeled_long_labels() {
+        // Labels of 32 bytes or more whose chunks exceed the field modulus.
+        for label in [&b"gkr_layer_3_claimed_sum_combined"[..], &[0xff; 64]] {
+            let elements = label_elements::<Fr>(label);
+            assert_eq!(elements.len(), 1 + label.len().div_ceil(31));
+
+            let mut labeled = PoseidonSponge::<Fr>::default();
+            let mut explicit = PoseidonSponge::<Fr>::default();
+            labeled.absorb_labeled(label, Fr::from(7));
+            explicit.absorb_elements(&[elements, vec![Fr::from(7)]].concat());
+            assert_eq!(labeled.squeeze(), explicit.squeeze());
+        }
+    }
+}
@@ -7,6 +7,16 @@ use itertools::Itertools;
 use poseidon::Poseidon;
 
 /// A Poseidon implementation of a transcript sponge.
+///
+/// The internal state of the underlying [Poseidon] sponge is private to the
+/// `poseidon` crate, so it can be neither exported as field elements nor
+/// restored from them. To carry a transcript from one proof to the next (e.g.
+/// in IVC), squeeze a digest and absorb it into a fresh sponge, as
+/// [TranscriptSponge::absorb_initialization_label] does.
+///
+/// For the same reason, squeezes cannot be rejection sampled: [Poseidon]
+/// only hands out its state already reduced to an element of `F`, whose
+/// encoding is always canonical, so there is no raw output left to reject.
 #[derive(Clone, Debug)]
 pub struct PoseidonSponge<F: Field> {
     /// The specific poseidon sponge configuration.
@@ -43,6 +53,53 @@ impl<F: Field> TranscriptSponge<F> for PoseidonSponge<F> {
     fn absorb_initialization_label(&mut self, label: &str) {
         let label_as_bytes = label.as_bytes();
         let label_as_field_elems = F::vec_from_bytes_le(label_as_bytes);
-        self.absorb_elements(&label_as_field_elems);
+        let mut label_hasher = Self::default();
+        label_hasher.absorb_elements(&label_as_field_elems);
+
+        // The state of the Poseidon sponge is not accessible, so the hash is
+        // absorbed into a fresh sponge rather than mixed into its state.
+        self.sponge = Self::default().sponge;
+        self.absorb(label_hasher.squeeze());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PoseidonSponge;
+    use crate::{transcript::TranscriptSponge, Fr};
+
+    #[test]
+    fn test_absorb_labeled_separates_labels() {
+        let challenge = |label: &[u8]| {
+            let mut sponge = PoseidonSponge::<Fr>::default();
+            sponge.absorb_labeled(label, Fr::from(7));
+            sponge.squeeze()
+        };
+        assert_ne!(challenge(b"a"), challenge(b"b"));
+        assert_eq!(challenge(b"a"), challenge(b"a"));
+    }
+
+    #[test]
+    fn test_initialization_label_forks_transcript() {
+        let challenges = |label: &str, history: &[Fr]| {
+            let mut sponge = PoseidonSponge::<Fr>::default();
+            sponge.absorb_elements(history);
+            sponge.absorb_initialization_label(label);
+            sponge.absorb(Fr::from(7));
+            sponge.squeeze_elements(2)
+        };
+
+        // Same label, same challenges, no matter what came before the label.
+        assert_eq!(challenges("sumcheck", &[]), challenges("sumcheck", &[]));
+        assert_eq!(
+            challenges("sumcheck", &[]),
+            challenges("sumcheck", &[Fr::from(1), Fr::from(2)])
+        );
+        assert_ne!(challenges("sumcheck", &[]), challenges("gkr", &[]));
+
+        // A labeled transcript differs from an unlabeled one.
+        let mut unlabeled = PoseidonSponge::<Fr>::default();
+        unlabeled.absorb(Fr::from(7));
+        assert_ne!(challenges("", &[]), unlabeled.squeeze_elements(2));
     }
 }
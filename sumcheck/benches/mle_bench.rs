@@ -0,0 +1,124 @@
+//! Benchmarks for the MLE operations on the critical path of sumcheck and
+//! GKR. Throughput is reported in field operations, counting one per table
+//! entry touched. Each benchmark draws its inputs from its own fixed-seed
+//! `test_rng`, so that runs are comparable while the tables within a run
+//! differ.
+
+use ark_std::{rand::RngCore, test_rng};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use shared_types::{ff_field, Fr};
+use sumcheck::mle::MultilinearExtension;
+
+fn random_mle(rng: &mut impl RngCore, num_vars: usize) -> MultilinearExtension<Fr> {
+    MultilinearExtension::new((0..1 << num_vars).map(|_| Fr::random(&mut *rng)).collect())
+}
+
+fn random_point(rng: &mut impl RngCore, num_vars: usize) -> Vec<Fr> {
+    (0..num_vars).map(|_| Fr::random(&mut *rng)).collect()
+}
+
+fn bench_evaluate(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let mut group = c.benchmark_group("evaluate");
+    group.sample_size(10);
+    for num_vars in (10..=25).step_by(5) {
+        let mle = random_mle(&mut rng, num_vars);
+        let point = random_point(&mut rng, num_vars);
+        group.throughput(Throughput::Elements(1 << num_vars));
+        group.bench_with_input(BenchmarkId::from_parameter(num_vars), &num_vars, |b, _| {
+            b.iter(|| mle.evaluate(&point))
+        });
+    }
+    group.finish();
+}
+
+/// f(`alpha`) as \sum_b eq(`alpha`, b) * f(b), via
+/// [MultilinearExtension::eq_weighted_sum] (formerly `weighted_sum_with_eq`),
+/// for comparison with `evaluate`.
+fn bench_weighted_sum_with_eq(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let mut group = c.benchmark_group("weighted_sum_with_eq");
+    group.sample_size(10);
+    for num_vars in (10..=25).step_by(5) {
+        let mle = random_mle(&mut rng, num_vars);
+        let alpha = random_point(&mut rng, num_vars);
+        group.throughput(Throughput::Elements(1 << num_vars));
+        group.bench_with_input(BenchmarkId::from_parameter(num_vars), &num_vars, |b, _| {
+            b.iter(|| mle.eq_weighted_sum(&alpha))
+        });
+    }
+    group.finish();
+}
+
+fn bench_restrict_first_var(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let mut group = c.benchmark_group("restrict_first_var");
+    group.sample_size(10);
+    for num_vars in (10..=24).step_by(2) {
+        let table = random_mle(&mut rng, num_vars).table().to_vec();
+        let r = random_point(&mut rng, 1)[0];
+        group.throughput(Throughput::Elements(1 << num_vars));
+        group.bench_with_input(BenchmarkId::from_parameter(num_vars), &num_vars, |b, _| {
+            b.iter_batched(
+                || table.clone(),
+                |mut table| MultilinearExtension::restrict_first_var(&mut table, r),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_pointwise_mul(c: &mut Criterion) {
+    const NUM_VARS: usize = 20;
+    let mut rng = test_rng();
+    let mles = [
+        random_mle(&mut rng, NUM_VARS),
+        random_mle(&mut rng, NUM_VARS),
+    ];
+    let mut group = c.benchmark_group("pointwise_mul");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1 << NUM_VARS));
+    group.bench_function(BenchmarkId::from_parameter(NUM_VARS), |b| {
+        b.iter(|| MultilinearExtension::product(&mles))
+    });
+    group.finish();
+}
+
+/// \sum_b \prod_k f_k(b), as computed by the sumcheck prover for its claimed
+/// sum, via the public [MultilinearExtension::product].
+fn bench_sum_over_hypercube(c: &mut Criterion) {
+    const NUM_VARS: usize = 18;
+    let mut rng = test_rng();
+    let mut group = c.benchmark_group("sum_over_hypercube");
+    group.sample_size(10);
+    for num_factors in [1, 2, 4] {
+        let mles: Vec<_> = (0..num_factors)
+            .map(|_| random_mle(&mut rng, NUM_VARS))
+            .collect();
+        group.throughput(Throughput::Elements((num_factors << NUM_VARS) as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_factors),
+            &num_factors,
+            |b, _| {
+                b.iter(|| {
+                    MultilinearExtension::product(&mles)
+                        .table()
+                        .iter()
+                        .fold(Fr::ZERO, |acc, x| acc + x)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_evaluate,
+    bench_weighted_sum_with_eq,
+    bench_restrict_first_var,
+    bench_pointwise_mul,
+    bench_sum_over_hypercube
+);
+criterion_main!(benches);
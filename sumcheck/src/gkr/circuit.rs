@@ -0,0 +1,1416 @@
+//! Layered arithmetic circuits, as proven by the GKR protocol.
+
+use super::GkrError;
+use crate::mle::MultilinearExtension;
+use ark_std::{log2, rand::RngCore};
+use shared_types::Field;
+
+/// The operation which a [Gate] applies to its input values. Constant gates
+/// read a single input and combine it with a constant fixed by the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GateType<F: Field> {
+    Add,
+    Mul,
+    ConstAdd(F),
+    ConstMul(F),
+    /// Looks up the entry of `table` whose index is the binary encoding of
+    /// the gate's `input_vars` (one or two) boolean inputs, the first of which
+    /// is the most significant bit. Not yet supported by the GKR prover.
+    Lookup {
+        table: MultilinearExtension<F>,
+        input_vars: usize,
+    },
+    /// Computes \sum_i c_i * v_i for each (input index i, coefficient c_i)
+    /// pair, ignoring the gate's `left` and `right`.
+    LinearCombination(Vec<(usize, F)>),
+}
+
+/// A single gate, reading its inputs from positions `left` and `right` of the
+/// previous layer. Constant gates only read from `left` and ignore `right`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gate<F: Field> {
+    pub gate_type: GateType<F>,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl<F: Field> Gate<F> {
+    pub fn new(gate_type: GateType<F>, left: usize, right: usize) -> Self {
+        Self {
+            gate_type,
+            left,
+            right,
+        }
+    }
+
+    /// A constant gate reading only from position `input` of the previous
+    /// layer.
+    pub fn constant(gate_type: GateType<F>, input: usize) -> Self {
+        debug_assert!(matches!(
+            gate_type,
+            GateType::ConstAdd(_) | GateType::ConstMul(_)
+        ));
+        Self::new(gate_type, input, input)
+    }
+
+    /// A gate computing \sum_i c_i * v_i over the (input index i, coefficient
+    /// c_i) pairs in `terms`.
+    pub fn linear_combination(terms: Vec<(usize, F)>) -> Self {
+        Self::new(GateType::LinearCombination(terms), 0, 0)
+    }
+
+    /// The positions of the previous layer which this gate reads from.
+    pub fn input_wires(&self) -> Vec<usize> {
+        match &self.gate_type {
+            GateType::Add | GateType::Mul => vec![self.left, self.right],
+            GateType::ConstAdd(_) | GateType::ConstMul(_) => vec![self.left],
+            GateType::Lookup { input_vars, .. } => {
+                assert!((1..=2).contains(input_vars));
+                [self.left, self.right][..*input_vars].to_vec()
+            }
+            GateType::LinearCombination(terms) => terms.iter().map(|(idx, _)| *idx).collect(),
+        }
+    }
+
+    /// The same gate, reading from position `f(i)` wherever this one reads
+    /// from position `i`.
+    fn map_wires(&self, f: impl Fn(usize) -> usize) -> Self {
+        match &self.gate_type {
+            GateType::LinearCombination(terms) => Self::linear_combination(
+                terms.iter().map(|(idx, coeff)| (f(*idx), *coeff)).collect(),
+            ),
+            gate_type => {
+                let wires = self.input_wires();
+                let left = f(wires[0]);
+                let right = wires.get(1).map_or(left, |&wire| f(wire));
+                Self::new(gate_type.clone(), left, right)
+            }
+        }
+    }
+
+    /// Computes this gate's output from the values of the previous layer.
+    pub fn evaluate(&self, inputs: &[F]) -> F {
+        match &self.gate_type {
+            GateType::Add => inputs[self.left] + inputs[self.right],
+            GateType::Mul => inputs[self.left] * inputs[self.right],
+            GateType::ConstAdd(constant) => inputs[self.left] + constant,
+            GateType::ConstMul(constant) => inputs[self.left] * constant,
+            GateType::Lookup { table, input_vars } => {
+                assert_eq!(table.num_vars(), *input_vars);
+                let idx = self.input_wires().iter().fold(0, |idx, &wire| {
+                    let bit = inputs[wire];
+                    assert!(bit == F::ZERO || bit == F::ONE);
+                    (idx << 1) | (bit == F::ONE) as usize
+                });
+                table.get(idx).unwrap()
+            }
+            GateType::LinearCombination(terms) => terms
+                .iter()
+                .fold(F::ZERO, |acc, (idx, coeff)| acc + inputs[*idx] * coeff),
+        }
+    }
+}
+
+/// The wiring predicates of a [GkrLayer], as MLEs over the bits of the output
+/// gate g followed by those of the inputs it reads:
+/// * `add` and `mul` over (g, x, y) are 1 exactly when gate g is of that type
+///   and reads inputs x and y.
+/// * `const_add` and `const_mul` over (g, x) are 1 exactly when gate g is of
+///   that type and reads input x.
+/// * `constants` over g holds the constant of gate g (zero for non-constant
+///   gates).
+/// * `linear_combination` over (g, x) holds the coefficient of input x if
+///   gate g is a linear combination gate, and zero otherwise.
+pub struct WiringPredicates<F: Field> {
+    pub add: MultilinearExtension<F>,
+    pub mul: MultilinearExtension<F>,
+    pub const_add: MultilinearExtension<F>,
+    pub const_mul: MultilinearExtension<F>,
+    pub constants: MultilinearExtension<F>,
+    pub linear_combination: MultilinearExtension<F>,
+}
+
+/// Which inputs each gate of a layer reads, for layers whose gates all have
+/// the same type (see [GkrLayer::from_wiring]). This is only a constructor
+/// convenience: the layer materialises its gates and does not keep the repr.
+pub enum GkrWiringRepr<F: Field> {
+    /// The MLEs over g of left(g) and right(g), the indices of the inputs read
+    /// by gate g, stored in full.
+    Dense(MultilinearExtension<F>, MultilinearExtension<F>),
+    /// A formula for (left(g), right(g)), e.g. for butterfly networks or
+    /// Merkle trees, evaluated once per gate when building the layer.
+    Structured(Box<dyn Fn(usize) -> (usize, usize)>),
+}
+
+impl<F: Field> GkrWiringRepr<F> {
+    /// The (left, right) inputs of gate `gate_idx`. Panics if a dense wiring
+    /// has no such gate or does not hold an index there.
+    pub fn inputs_of(&self, gate_idx: usize) -> (usize, usize) {
+        match self {
+            GkrWiringRepr::Dense(left, right) => (
+                field_to_index(left[gate_idx]),
+                field_to_index(right[gate_idx]),
+            ),
+            GkrWiringRepr::Structured(wiring) => wiring(gate_idx),
+        }
+    }
+}
+
+/// The index which `value` encodes as a small integer.
+fn field_to_index<F: Field>(value: F) -> usize {
+    let bytes = value.to_canonical_bytes_le();
+    assert!(bytes[8..].iter().all(|&byte| byte == 0), "Not an index");
+    u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize
+}
+
+/// A single layer of a [GkrCircuit]. The `i`-th gate computes the `i`-th
+/// value of this layer from the `num_input_gates` values of the previous
+/// layer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GkrLayer<F: Field> {
+    gates: Vec<Gate<F>>,
+    num_input_gates: usize,
+}
+
+impl<F: Field> GkrLayer<F> {
+    pub fn new(gates: Vec<Gate<F>>, num_input_gates: usize) -> Self {
+        Self {
+            gates,
+            num_input_gates,
+        }
+    }
+
+    /// Like [GkrLayer::new], but checks the wiring with
+    /// [GkrLayer::check_wiring] first.
+    pub fn try_new(gates: Vec<Gate<F>>, num_input_gates: usize) -> Result<Self, GkrError> {
+        let layer = Self::new(gates, num_input_gates);
+        layer.check_wiring()?;
+        Ok(layer)
+    }
+
+    /// Checks that every gate only reads from indices less than
+    /// `num_input_gates`. On failure, `max_idx` is `num_input_gates`.
+    pub fn check_wiring(&self) -> Result<(), GkrError> {
+        for (gate_idx, gate) in self.gates.iter().enumerate() {
+            if let Some(input_idx) = gate
+                .input_wires()
+                .into_iter()
+                .find(|&input_idx| input_idx >= self.num_input_gates)
+            {
+                return Err(GkrError::OutOfBoundsWiring {
+                    gate_idx,
+                    input_idx,
+                    max_idx: self.num_input_gates,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// A layer of `num_gates` gates of type `gate_type`, each reading the
+    /// inputs which `wiring` gives for it. Only useful for two-input gates.
+    pub fn from_wiring(
+        gate_type: GateType<F>,
+        wiring: &GkrWiringRepr<F>,
+        num_gates: usize,
+        num_input_gates: usize,
+    ) -> Self {
+        let gates = (0..num_gates)
+            .map(|gate_idx| {
+                let (left, right) = wiring.inputs_of(gate_idx);
+                Gate::new(gate_type.clone(), left, right)
+            })
+            .collect();
+        Self::new(gates, num_input_gates)
+    }
+
+    pub fn gates(&self) -> &[Gate<F>] {
+        &self.gates
+    }
+
+    /// Number of gates (and therefore values) within this layer.
+    pub fn num_gates(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Number of values this layer expects from the previous layer.
+    pub fn num_input_gates(&self) -> usize {
+        self.num_input_gates
+    }
+
+    /// Computes this layer's values from the values of the previous layer.
+    pub fn evaluate(&self, inputs: &[F]) -> Vec<F> {
+        self.gates
+            .iter()
+            .map(|gate| gate.evaluate(inputs))
+            .collect()
+    }
+
+    /// A layer of copy gates, the `k`-th of which outputs the value at position
+    /// `sources[k]` of the previous layer, or zero if `sources[k]` is `None`.
+    /// Padded up to a power of two with gates which output zero.
+    pub fn copy_layer(sources: &[Option<usize>], num_input_gates: usize) -> Self {
+        let width = sources.len().next_power_of_two();
+        let gates = (0..width)
+            .map(|k| match sources.get(k).copied().flatten() {
+                Some(source) => Gate::constant(GateType::ConstMul(F::ONE), source),
+                None => Gate::constant(GateType::ConstMul(F::ZERO), 0),
+            })
+            .collect();
+        Self::new(gates, num_input_gates)
+    }
+
+    /// Whether this layer's values do not depend on the previous layer, i.e.
+    /// every gate is a `ConstMul` gate with constant zero.
+    pub fn is_constant_layer(&self) -> bool {
+        self.gates.iter().all(|gate| match &gate.gate_type {
+            GateType::ConstMul(constant) => *constant == F::ZERO,
+            _ => false,
+        })
+    }
+
+    /// Whether some value of the previous layer is read more than once by
+    /// this layer's gates.
+    pub fn has_fan_out(&self) -> bool {
+        let mut reads = vec![0usize; self.num_input_gates];
+        self.gates
+            .iter()
+            .flat_map(|gate| gate.input_wires())
+            .any(|wire| {
+                reads[wire] += 1;
+                reads[wire] > 1
+            })
+    }
+
+    /// The [WiringPredicates] of this layer. Assumes the layer's widths are
+    /// powers of two. Panics if the layer has a lookup gate.
+    pub fn wiring_predicates(&self) -> WiringPredicates<F> {
+        let num_gate_vars = log2(self.num_gates()) as usize;
+        let num_input_vars = log2(self.num_input_gates) as usize;
+        let mut add_table = vec![F::ZERO; 1 << (num_gate_vars + 2 * num_input_vars)];
+        let mut mul_table = add_table.clone();
+        let mut const_add_table = vec![F::ZERO; 1 << (num_gate_vars + num_input_vars)];
+        let mut const_mul_table = const_add_table.clone();
+        let mut linear_combination_table = const_add_table.clone();
+        let mut constants = vec![F::ZERO; 1 << num_gate_vars];
+        for (gate_idx, gate) in self.gates.iter().enumerate() {
+            let const_idx = (gate_idx << num_input_vars) + gate.left;
+            let idx = (const_idx << num_input_vars) + gate.right;
+            match &gate.gate_type {
+                GateType::Add => add_table[idx] = F::ONE,
+                GateType::Mul => mul_table[idx] = F::ONE,
+                GateType::ConstAdd(constant) => {
+                    const_add_table[const_idx] = F::ONE;
+                    constants[gate_idx] = *constant;
+                }
+                GateType::ConstMul(constant) => {
+                    const_mul_table[const_idx] = F::ONE;
+                    constants[gate_idx] = *constant;
+                }
+                GateType::Lookup { .. } => {
+                    panic!("Lookup gates have no wiring predicates, see GkrError::UnsupportedGate")
+                }
+                GateType::LinearCombination(terms) => {
+                    for (input_idx, coeff) in terms {
+                        linear_combination_table[(gate_idx << num_input_vars) + input_idx] += coeff;
+                    }
+                }
+            }
+        }
+        WiringPredicates {
+            add: MultilinearExtension::new(add_table),
+            mul: MultilinearExtension::new(mul_table),
+            const_add: MultilinearExtension::new(const_add_table),
+            const_mul: MultilinearExtension::new(const_mul_table),
+            constants: MultilinearExtension::new(constants),
+            linear_combination: MultilinearExtension::new(linear_combination_table),
+        }
+    }
+}
+
+/// Whether a value of the input layer is part of the prover's private witness
+/// or a public input, which is absorbed into the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputType {
+    Witness,
+    Public,
+}
+
+/// A layered arithmetic circuit together with the values of its input layer.
+///
+/// `layers[0]` reads from the input layer and each subsequent layer reads from
+/// the one before it; the values of the last layer are the circuit's output.
+/// `input_types` holds the [InputType] of each input value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GkrCircuit<F: Field> {
+    input: Vec<F>,
+    input_types: Vec<InputType>,
+    layers: Vec<GkrLayer<F>>,
+}
+
+impl<F: Field> GkrCircuit<F> {
+    /// A circuit whose input values are all witness values.
+    pub fn new(input: Vec<F>, layers: Vec<GkrLayer<F>>) -> Self {
+        Self {
+            input_types: vec![InputType::Witness; input.len()],
+            input,
+            layers,
+        }
+    }
+
+    /// Sets the [InputType] of each input value. Panics unless there is one
+    /// per input value.
+    pub fn with_input_types(mut self, input_types: Vec<InputType>) -> Self {
+        assert_eq!(input_types.len(), self.input.len());
+        self.input_types = input_types;
+        self
+    }
+
+    pub fn input(&self) -> &[F] {
+        &self.input
+    }
+
+    pub fn input_types(&self) -> &[InputType] {
+        &self.input_types
+    }
+
+    /// The (index, value) pairs of the public input values.
+    pub fn public_inputs(&self) -> Vec<(usize, F)> {
+        self.input
+            .iter()
+            .zip(&self.input_types)
+            .enumerate()
+            .filter(|(_, (_, &input_type))| input_type == InputType::Public)
+            .map(|(idx, (&value, _))| (idx, value))
+            .collect()
+    }
+
+    /// Sets the first `inputs.len()` values of the input layer and marks them
+    /// as public. The verifier knows the input layer and checks the final
+    /// claim of a GKR proof against it directly, so public inputs are bound to
+    /// the proof without the prover committing to them. Panics if there are
+    /// more public inputs than input values.
+    pub fn set_public_inputs(&mut self, inputs: &[F]) {
+        assert!(inputs.len() <= self.input.len());
+        self.input[..inputs.len()].copy_from_slice(inputs);
+        self.input_types[..inputs.len()].fill(InputType::Public);
+    }
+
+    /// The values of the output layer, which are public: [super::gkr_verify]
+    /// checks a proof against them.
+    pub fn get_public_output(&self) -> Vec<F> {
+        self.evaluate()
+    }
+
+    pub fn layers(&self) -> &[GkrLayer<F>] {
+        &self.layers
+    }
+
+    /// Number of layers above the input layer.
+    pub fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// A single-layer circuit on `input` which copies the value at `gate_idx`
+    /// of the input to every position in its list of output destinations, for
+    /// each (`gate_idx`, destinations) pair in `wires`. Positions which no
+    /// wire reaches are zero.
+    ///
+    /// Panics if two wires share an output destination.
+    pub fn fan_out_circuit(input: Vec<F>, wires: &[(usize, Vec<usize>)]) -> Self {
+        let width = wires
+            .iter()
+            .flat_map(|(_, destinations)| destinations)
+            .map(|destination| destination + 1)
+            .max()
+            .unwrap_or(1);
+        let mut sources = vec![None; width];
+        for (gate_idx, destinations) in wires {
+            for &destination in destinations {
+                assert!(sources[destination].is_none());
+                sources[destination] = Some(*gate_idx);
+            }
+        }
+        let layer = GkrLayer::copy_layer(&sources, input.len());
+        Self::new(input, vec![layer])
+    }
+
+    /// Returns an equivalent circuit in which every layer reads each value of
+    /// the layer below it at most once. Below each layer with fan-out, a copy
+    /// layer is inserted which duplicates every value once per read, and the
+    /// layer is rewired to read from the copies. All fan-out thus happens
+    /// within the copy layers.
+    pub fn allow_fan_out(&self) -> Self {
+        let mut layers = Vec::with_capacity(2 * self.depth());
+        for layer in &self.layers {
+            if !layer.has_fan_out() {
+                layers.push(layer.clone());
+                continue;
+            }
+
+            let sources: Vec<Option<usize>> = layer
+                .gates
+                .iter()
+                .flat_map(|gate| gate.input_wires())
+                .map(Some)
+                .collect();
+            let copies = GkrLayer::copy_layer(&sources, layer.num_input_gates);
+            let mut next_copy = 0;
+            let gates = layer
+                .gates
+                .iter()
+                .map(|gate| {
+                    let left = next_copy;
+                    let num_wires = gate.input_wires().len();
+                    next_copy += num_wires;
+                    match &gate.gate_type {
+                        GateType::LinearCombination(terms) => Gate::linear_combination(
+                            (left..)
+                                .zip(terms.iter().map(|(_, coeff)| *coeff))
+                                .collect(),
+                        ),
+                        gate_type => Gate::new(gate_type.clone(), left, left + num_wires - 1),
+                    }
+                })
+                .collect();
+            let rewired = GkrLayer::new(gates, copies.num_gates());
+            layers.push(copies);
+            layers.push(rewired);
+        }
+        Self::new(self.input.clone(), layers).with_input_types(self.input_types.clone())
+    }
+
+    /// Compiles a circuit with skip connections into a layered one. The gates
+    /// of `layers[j]` may read from any layer below them, not just the one
+    /// directly below: their wire indices are positions in the concatenation
+    /// of the values of the input layer, `layers[0]`, ..., `layers[j - 1]`.
+    ///
+    /// Values read by a layer other than the one directly above them are
+    /// carried up by identity-copy gates, which are appended to each
+    /// intermediate layer after its own gates; the gates are rewired to read
+    /// from the copies. Every layer is then padded up to a power of two with
+    /// gates which output zero. The first `layers[j].len()` values of layer
+    /// `j` of the result are the values of `layers[j]`.
+    ///
+    /// Panics if a gate reads from a position at or above its own layer.
+    pub fn with_skip_connections(input: Vec<F>, layers: Vec<Vec<Gate<F>>>) -> Self {
+        // `offsets[l]` is the position of the first value of layer `l` in the
+        // concatenation, where layer 0 is the input layer.
+        let mut offsets = vec![0, input.len()];
+        for gates in &layers {
+            offsets.push(offsets.last().unwrap() + gates.len());
+        }
+
+        // The last layer which reads each value.
+        let mut last_use = vec![0; *offsets.last().unwrap()];
+        for (j, gates) in layers.iter().enumerate() {
+            for wire in gates.iter().flat_map(|gate| gate.input_wires()) {
+                assert!(wire < offsets[j + 1], "gate of layer {} reads {}", j, wire);
+                last_use[wire] = j + 1;
+            }
+        }
+
+        // `positions[wire]` is the position of a value in the layer being
+        // built on, if it is present there.
+        let mut positions: Vec<Option<usize>> = (0..last_use.len())
+            .map(|wire| (wire < input.len()).then_some(wire))
+            .collect();
+        let mut num_input_gates = input.len();
+        let mut compiled = Vec::with_capacity(layers.len());
+        for (j, gates) in layers.iter().enumerate() {
+            let layer = j + 1;
+            let position_of = |wire: usize| positions[wire].unwrap();
+            let mut layer_gates: Vec<Gate<F>> = gates
+                .iter()
+                .map(|gate| gate.map_wires(position_of))
+                .collect();
+            let carried: Vec<usize> = (0..offsets[layer])
+                .filter(|&wire| last_use[wire] > layer)
+                .collect();
+            layer_gates.extend(
+                carried
+                    .iter()
+                    .map(|&wire| Gate::constant(GateType::ConstMul(F::ONE), position_of(wire))),
+            );
+            let width = layer_gates.len().next_power_of_two();
+            layer_gates.resize(width, Gate::constant(GateType::ConstMul(F::ZERO), 0));
+
+            positions.fill(None);
+            for k in 0..gates.len() {
+                positions[offsets[layer] + k] = Some(k);
+            }
+            for (slot, &wire) in carried.iter().enumerate() {
+                positions[wire] = Some(gates.len() + slot);
+            }
+            compiled.push(GkrLayer::new(layer_gates, num_input_gates));
+            num_input_gates = width;
+        }
+        Self::new(input, compiled)
+    }
+
+    /// Sorts gates defined in any order into layers. IDs `0..input.len()` refer
+    /// to the input values and ID `input.len() + i` to `gates[i]`, a
+    /// (gate type, left, right) triple reading the values with IDs left and
+    /// right, or those in its terms for a linear combination gate. Each gate is
+    /// placed at layer max(layer(left), layer(right)) + 1, where the input
+    /// layer is layer 0, and the layers are compiled by
+    /// [GkrCircuit::with_skip_connections].
+    ///
+    /// The output is the values of the gates which no other gate reads, in ID
+    /// order, padded with zeros up to a power of two; those below the top
+    /// layer are copied up to it. Returns an error if a gate reads an ID which
+    /// does not exist or if the gates contain a cycle.
+    pub fn topological_sort_and_layer(
+        input: Vec<F>,
+        gates: &[(GateType<F>, usize, usize)],
+    ) -> Result<Self, GkrError> {
+        if gates.is_empty() {
+            return Err(GkrError::EmptyCircuit);
+        }
+        let num_inputs = input.len();
+        let num_ids = num_inputs + gates.len();
+        let gates: Vec<Gate<F>> = gates
+            .iter()
+            .map(|(gate_type, left, right)| Gate::new(gate_type.clone(), *left, *right))
+            .collect();
+
+        // The gates reading each ID, and the number of gate inputs of each gate
+        // whose layer is not yet known.
+        let mut readers = vec![vec![]; num_ids];
+        let mut num_unsorted_inputs = vec![0; gates.len()];
+        for (i, gate) in gates.iter().enumerate() {
+            for wire in gate.input_wires() {
+                if wire >= num_ids {
+                    return Err(GkrError::OutOfBoundsWiring {
+                        gate_idx: num_inputs + i,
+                        input_idx: wire,
+                        max_idx: num_ids,
+                    });
+                }
+                readers[wire].push(i);
+                if wire >= num_inputs {
+                    num_unsorted_inputs[i] += 1;
+                }
+            }
+        }
+
+        // Kahn's algorithm: a gate's layer is final once all of its gate inputs
+        // have been sorted.
+        let mut layer_of = vec![0; num_ids];
+        layer_of[num_inputs..].fill(1);
+        let mut ready: Vec<usize> = (0..gates.len())
+            .filter(|&i| num_unsorted_inputs[i] == 0)
+            .collect();
+        let mut num_sorted = 0;
+        while let Some(i) = ready.pop() {
+            num_sorted += 1;
+            for &reader in &readers[num_inputs + i] {
+                let layer = layer_of[num_inputs + i] + 1;
+                layer_of[num_inputs + reader] = layer_of[num_inputs + reader].max(layer);
+                num_unsorted_inputs[reader] -= 1;
+                if num_unsorted_inputs[reader] == 0 {
+                    ready.push(reader);
+                }
+            }
+        }
+        if num_sorted < gates.len() {
+            let i = (0..gates.len())
+                .find(|&i| num_unsorted_inputs[i] > 0)
+                .unwrap();
+            return Err(GkrError::CyclicWiring {
+                gate_id: num_inputs + i,
+            });
+        }
+
+        let depth = *layer_of.iter().max().unwrap();
+        let mut ids_by_layer = vec![vec![]; depth];
+        for id in num_inputs..num_ids {
+            ids_by_layer[layer_of[id] - 1].push(id);
+        }
+        // The position of each ID in the concatenation of the layers.
+        let mut positions: Vec<usize> = (0..num_ids).collect();
+        let mut offset = num_inputs;
+        for ids in &ids_by_layer {
+            for (k, &id) in ids.iter().enumerate() {
+                positions[id] = offset + k;
+            }
+            offset += ids.len();
+        }
+        let layer_gate = |id: usize| gates[id - num_inputs].map_wires(|wire| positions[wire]);
+
+        let mut layers: Vec<Vec<Gate<F>>> = ids_by_layer[..depth - 1]
+            .iter()
+            .map(|ids| ids.iter().map(|&id| layer_gate(id)).collect())
+            .collect();
+        layers.push(
+            (num_inputs..num_ids)
+                .filter(|&id| readers[id].is_empty())
+                .map(|id| {
+                    if layer_of[id] == depth {
+                        layer_gate(id)
+                    } else {
+                        Gate::constant(GateType::ConstMul(F::ONE), positions[id])
+                    }
+                })
+                .collect(),
+        );
+        Ok(Self::with_skip_connections(input, layers))
+    }
+
+    /// Computes the output of the circuit on its input.
+    pub fn evaluate(&self) -> Vec<F> {
+        self.evaluate_with_trace().0
+    }
+
+    /// Computes the output of the circuit on its input, along with the values
+    /// at every layer: `trace[0]` is the input layer and `trace[depth()]` the
+    /// output layer.
+    pub fn evaluate_with_trace(&self) -> (Vec<F>, Vec<Vec<F>>) {
+        let mut trace = vec![self.input.clone()];
+        for layer in &self.layers {
+            let next = layer.evaluate(trace.last().unwrap());
+            trace.push(next);
+        }
+        (trace.last().unwrap().clone(), trace)
+    }
+
+    /// Replaces the circuit's input with `input`, which is known when the
+    /// circuit is constructed, and pre-evaluates the leading layers which can
+    /// be computed from it without any witness value: a layer is folded if
+    /// none of its gates (transitively) reads an [InputType::Witness] input,
+    /// and so is every layer below it. The output layer is always kept so
+    /// that the result is still a circuit GKR can prove. Returns the shorter
+    /// circuit along with its new input, the pre-computed values of the
+    /// deepest folded layer, which are public since they only depend on
+    /// public inputs. If nothing can be folded, returns the circuit on `input`
+    /// unchanged.
+    ///
+    /// Panics if `input` does not have as many values as the circuit's input.
+    pub fn constant_fold(mut self, input: &[F]) -> (GkrCircuit<F>, Vec<F>) {
+        assert_eq!(input.len(), self.input.len());
+        self.input = input.to_vec();
+
+        // Whether each value of the current layer depends on a witness value.
+        let mut witness_dependent: Vec<bool> = self
+            .input_types
+            .iter()
+            .map(|&input_type| input_type == InputType::Witness)
+            .collect();
+        let mut num_folded = 0;
+        for layer in &self.layers[..self.depth().saturating_sub(1)] {
+            witness_dependent = layer
+                .gates
+                .iter()
+                .map(|gate| {
+                    gate.input_wires()
+                        .iter()
+                        .any(|&wire| witness_dependent[wire])
+                })
+                .collect();
+            if witness_dependent.contains(&true) {
+                break;
+            }
+            num_folded += 1;
+        }
+
+        if num_folded == 0 {
+            let input = self.input.clone();
+            return (self, input);
+        }
+        let (_, mut trace) = self.evaluate_with_trace();
+        let folded_input = trace.swap_remove(num_folded);
+        let layers = self.layers.split_off(num_folded);
+        let input_types = vec![InputType::Public; folded_input.len()];
+        let folded = Self::new(folded_input.clone(), layers).with_input_types(input_types);
+        (folded, folded_input)
+    }
+
+    /// Checks that the circuit can be proven with GKR, i.e. that
+    /// * it has at least one layer above the input layer,
+    /// * the input layer and every other layer have a power-of-two width,
+    /// * each layer's expected input width matches the width of the layer
+    ///   below it,
+    /// * every gate only reads from indices which exist in the layer below it,
+    ///   and
+    /// * no gate is a lookup, which the GKR prover does not support yet.
+    pub fn check_well_formed(&self) -> Result<(), GkrError> {
+        if self.layers.is_empty() {
+            return Err(GkrError::EmptyCircuit);
+        }
+        if !self.input.len().is_power_of_two() {
+            return Err(GkrError::NonPowerOfTwoInputWidth(self.input.len()));
+        }
+        if self.layers[0].num_input_gates() != self.input.len() {
+            return Err(GkrError::InputWidthMismatch {
+                input_width: self.input.len(),
+                expected: self.layers[0].num_input_gates(),
+            });
+        }
+
+        for (layer_idx, layer) in self.layers.iter().enumerate() {
+            if !layer.num_gates().is_power_of_two() {
+                return Err(GkrError::NonPowerOfTwoLayerWidth {
+                    layer_idx,
+                    width: layer.num_gates(),
+                });
+            }
+            if layer_idx > 0 && layer.num_input_gates() != self.layers[layer_idx - 1].num_gates() {
+                return Err(GkrError::LayerWidthMismatch {
+                    layer_idx,
+                    expected: layer.num_input_gates(),
+                    actual: self.layers[layer_idx - 1].num_gates(),
+                });
+            }
+            if let Err(GkrError::OutOfBoundsWiring {
+                gate_idx,
+                input_idx,
+                max_idx,
+            }) = layer.check_wiring()
+            {
+                return Err(GkrError::InvalidWiring {
+                    layer_idx,
+                    gate_idx,
+                    input_idx,
+                    num_input_gates: max_idx,
+                });
+            }
+            if let Some(gate_idx) = layer
+                .gates
+                .iter()
+                .position(|gate| matches!(gate.gate_type, GateType::Lookup { .. }))
+            {
+                return Err(GkrError::UnsupportedGate {
+                    layer_idx,
+                    gate_idx,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a [GkrCircuit] from its input layer upwards, one layer of add or mul
+/// gates at a time, each reading the layer added before it.
+///
+/// ```
+/// use shared_types::Fr;
+/// use sumcheck::gkr::circuit::GkrCircuitBuilder;
+///
+/// // ((a * b) + (c * d)) * ((e * f) + (g * h))
+/// let circuit = GkrCircuitBuilder::new()
+///     .add_input_layer((1..=8).map(Fr::from).collect())
+///     .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+///     .add_add_layer(vec![(0, 1), (2, 3)])
+///     .add_mul_layer(vec![(0, 1)])
+///     .build()
+///     .unwrap();
+/// assert_eq!(circuit.evaluate(), vec![Fr::from(14 * 86)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GkrCircuitBuilder<F: Field> {
+    input: Vec<F>,
+    layers: Vec<GkrLayer<F>>,
+}
+
+impl<F: Field> Default for GkrCircuitBuilder<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> GkrCircuitBuilder<F> {
+    pub fn new() -> Self {
+        Self {
+            input: vec![],
+            layers: vec![],
+        }
+    }
+
+    /// Sets the values of the input layer.
+    pub fn add_input_layer(mut self, values: Vec<F>) -> Self {
+        self.input = values;
+        self
+    }
+
+    /// Adds a layer of mul gates, one per (left, right) pair in `wiring`.
+    pub fn add_mul_layer(self, wiring: Vec<(usize, usize)>) -> Self {
+        self.add_layer(GateType::Mul, wiring)
+    }
+
+    /// Adds a layer of add gates, one per (left, right) pair in `wiring`.
+    pub fn add_add_layer(self, wiring: Vec<(usize, usize)>) -> Self {
+        self.add_layer(GateType::Add, wiring)
+    }
+
+    fn add_layer(mut self, gate_type: GateType<F>, wiring: Vec<(usize, usize)>) -> Self {
+        let num_input_gates = self
+            .layers
+            .last()
+            .map_or(self.input.len(), |layer| layer.num_gates());
+        let gates = wiring
+            .into_iter()
+            .map(|(left, right)| Gate::new(gate_type.clone(), left, right))
+            .collect();
+        self.layers.push(GkrLayer::new(gates, num_input_gates));
+        self
+    }
+
+    /// The circuit built so far, if it is well-formed.
+    pub fn build(self) -> Result<GkrCircuit<F>, GkrError> {
+        let circuit = GkrCircuit::new(self.input, self.layers);
+        circuit.check_well_formed()?;
+        Ok(circuit)
+    }
+}
+
+/// A random well-formed circuit with `depth` layers above an input layer,
+/// each `2^log_width` wide. Every gate is an add or a mul gate with equal
+/// probability, reading two uniformly random gates of the layer below it.
+pub fn random_circuit<F: Field>(
+    depth: usize,
+    log_width: usize,
+    rng: &mut impl RngCore,
+) -> GkrCircuit<F> {
+    assert!(depth > 0);
+    let width = 1 << log_width;
+    let input = (0..width).map(|_| F::random(&mut *rng)).collect();
+    let layers = (0..depth)
+        .map(|_| {
+            let gates = (0..width)
+                .map(|_| {
+                    let gate_type = if rng.next_u32() & 1 == 0 {
+                        GateType::Add
+                    } else {
+                        GateType::Mul
+                    };
+                    let left = rng.next_u64() as usize % width;
+                    let right = rng.next_u64() as usize % width;
+                    Gate::new(gate_type, left, right)
+                })
+                .collect();
+            GkrLayer::new(gates, width)
+        })
+        .collect();
+    GkrCircuit::new(input, layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gkr::{
+            circuit::{
+                Gate, GateType, GkrCircuit, GkrCircuitBuilder, GkrLayer, GkrWiringRepr, InputType,
+            },
+            GkrError,
+        },
+        mle::MultilinearExtension,
+    };
+    use ark_std::{
+        rand::{seq::SliceRandom, Rng},
+        test_rng,
+    };
+    use shared_types::{ff_field, Fr};
+
+    fn input(width: u64) -> Vec<Fr> {
+        (1..=width).map(Fr::from).collect()
+    }
+
+    /// Layer of `num_gates` multiplication gates, gate `i` reading inputs
+    /// `2i` and `2i + 1`.
+    fn pairwise_mul_layer(num_gates: usize) -> GkrLayer<Fr> {
+        let gates = (0..num_gates)
+            .map(|i| Gate::new(GateType::Mul, 2 * i, 2 * i + 1))
+            .collect();
+        GkrLayer::new(gates, 2 * num_gates)
+    }
+
+    #[test]
+    fn test_well_formed_circuit() {
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer(input(8))
+            .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+            .add_mul_layer(vec![(0, 1), (2, 3)])
+            .build()
+            .unwrap();
+        assert_eq!(
+            circuit,
+            GkrCircuit::new(input(8), vec![pairwise_mul_layer(4), pairwise_mul_layer(2)])
+        );
+        assert_eq!(circuit.evaluate(), vec![Fr::from(24), Fr::from(1680)]);
+    }
+
+    #[test]
+    fn test_evaluate_with_trace() {
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer(input(8))
+            .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+            .add_add_layer(vec![(0, 1), (2, 3)])
+            .build()
+            .unwrap();
+        let (output, trace) = circuit.evaluate_with_trace();
+        assert_eq!(trace.len(), circuit.depth() + 1);
+        assert_eq!(trace[0], input(8));
+        assert_eq!(trace[1], [2, 12, 30, 56].map(Fr::from));
+        assert_eq!(trace[circuit.depth()], output);
+        assert_eq!(output, circuit.evaluate());
+        assert_eq!(output, [14, 86].map(Fr::from));
+    }
+
+    #[test]
+    fn test_constant_fold() {
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer(vec![Fr::from(0); 8])
+            .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+            .add_add_layer(vec![(0, 1), (2, 3)])
+            .add_mul_layer(vec![(0, 1), (1, 0)])
+            .build()
+            .unwrap();
+
+        // All inputs public: every layer but the output layer is folded.
+        let public = circuit.clone().with_input_types(vec![InputType::Public; 8]);
+        let (folded, folded_input) = public.constant_fold(&input(8));
+        assert_eq!(folded.depth(), circuit.depth() - 2);
+        assert_eq!(folded_input, [14, 86].map(Fr::from));
+        assert_eq!(folded.input(), &folded_input[..]);
+        assert_eq!(folded.input_types(), [InputType::Public; 2]);
+        assert_eq!(folded.check_well_formed(), Ok(()));
+        assert_eq!(folded.evaluate(), vec![Fr::from(14 * 86); 2]);
+
+        // A witness input read by the first layer blocks all folding.
+        let mut input_types = vec![InputType::Public; 8];
+        input_types[7] = InputType::Witness;
+        let (folded, folded_input) = circuit
+            .clone()
+            .with_input_types(input_types.clone())
+            .constant_fold(&input(8));
+        assert_eq!(folded.depth(), circuit.depth());
+        assert_eq!(folded_input, input(8));
+        assert_eq!(folded.input_types(), input_types);
+        assert_eq!(folded.evaluate(), vec![Fr::from(14 * 86); 2]);
+
+        // A witness input which no gate reads does not block folding.
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer(input(4))
+            .add_mul_layer(vec![(0, 1), (2, 2)])
+            .add_add_layer(vec![(0, 1), (1, 0)])
+            .add_mul_layer(vec![(0, 1), (1, 0)])
+            .build()
+            .unwrap()
+            .with_input_types(vec![
+                InputType::Public,
+                InputType::Public,
+                InputType::Public,
+                InputType::Witness,
+            ]);
+        let (folded, folded_input) = circuit.clone().constant_fold(&input(4));
+        assert_eq!(folded.depth(), circuit.depth() - 2);
+        assert_eq!(folded_input, [11, 11].map(Fr::from));
+        assert_eq!(folded.evaluate(), circuit.evaluate());
+    }
+
+    #[test]
+    fn test_builder_rejects_malformed_circuits() {
+        let result = GkrCircuitBuilder::<Fr>::new()
+            .add_input_layer(input(4))
+            .build();
+        assert_eq!(result.unwrap_err(), GkrError::EmptyCircuit);
+
+        let result = GkrCircuitBuilder::new()
+            .add_input_layer(input(4))
+            .add_add_layer(vec![(0, 1), (2, 4)])
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            GkrError::InvalidWiring {
+                layer_idx: 0,
+                gate_idx: 1,
+                input_idx: 4,
+                num_input_gates: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_layer_wiring_validation() {
+        let gates = vec![
+            Gate::new(GateType::Add, 0, 1),
+            Gate::new(GateType::Mul, 3, 2),
+        ];
+        let layer = GkrLayer::<Fr>::try_new(gates.clone(), 4).unwrap();
+        assert_eq!(layer.check_wiring(), Ok(()));
+
+        assert_eq!(
+            GkrLayer::<Fr>::try_new(gates, 3).unwrap_err(),
+            GkrError::OutOfBoundsWiring {
+                gate_idx: 1,
+                input_idx: 3,
+                max_idx: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_structured_wiring() {
+        // Each stage of a butterfly network adds the values `stride` apart.
+        for stride in [1, 2, 4] {
+            let structured = GkrWiringRepr::Structured(Box::new(move |g| (g, g ^ stride)));
+            let dense = GkrWiringRepr::Dense(
+                MultilinearExtension::new((0..8).map(Fr::from).collect()),
+                MultilinearExtension::new((0..8).map(|g| Fr::from(g ^ stride as u64)).collect()),
+            );
+
+            let from_structured = GkrLayer::from_wiring(GateType::Add, &structured, 8, 8);
+            let from_dense = GkrLayer::from_wiring(GateType::Add, &dense, 8, 8);
+            assert_eq!(from_structured, from_dense);
+            assert_eq!(
+                from_structured.evaluate(&input(8)),
+                from_dense.evaluate(&input(8))
+            );
+            assert_eq!(
+                from_structured.wiring_predicates().add,
+                from_dense.wiring_predicates().add
+            );
+
+            let gate = &from_structured.gates()[0];
+            assert_eq!((gate.left, gate.right), (0, stride));
+        }
+    }
+
+    #[test]
+    fn test_const_mul_layer() {
+        let constants: Vec<Fr> = [3, 5, 7, 11].into_iter().map(Fr::from).collect();
+        let layer = GkrLayer::new(
+            constants
+                .iter()
+                .enumerate()
+                .map(|(i, &constant)| Gate::constant(GateType::ConstMul(constant), i))
+                .collect(),
+            4,
+        );
+        let inputs = input(4);
+        let circuit = GkrCircuit::new(inputs.clone(), vec![layer]);
+        assert_eq!(circuit.check_well_formed(), Ok(()));
+
+        let expected: Vec<Fr> = inputs
+            .iter()
+            .zip(&constants)
+            .map(|(input, constant)| *input * constant)
+            .collect();
+        assert_eq!(circuit.evaluate(), expected);
+    }
+
+    #[test]
+    fn test_linear_combination_layer() {
+        // 3a + 5b - 2c and a + d over inputs (a, b, c, d) = (1, 2, 3, 4).
+        let layer = GkrLayer::new(
+            vec![
+                Gate::linear_combination(vec![
+                    (0, Fr::from(3)),
+                    (1, Fr::from(5)),
+                    (2, -Fr::from(2)),
+                ]),
+                Gate::linear_combination(vec![(0, Fr::ONE), (3, Fr::ONE)]),
+            ],
+            4,
+        );
+        let circuit = GkrCircuit::new(input(4), vec![layer.clone()]);
+        assert_eq!(circuit.check_well_formed(), Ok(()));
+        assert_eq!(circuit.evaluate(), vec![Fr::from(3 + 10 - 6), Fr::from(5)]);
+
+        // The predicate over (g, x) holds the coefficient of input x in gate g.
+        let predicate = layer.wiring_predicates().linear_combination;
+        assert_eq!(predicate.get(0b0_10), Some(-Fr::from(2)));
+        assert_eq!(predicate.get(0b1_11), Some(Fr::ONE));
+        assert_eq!(predicate.get(0b1_01), Some(Fr::ZERO));
+    }
+
+    #[test]
+    fn test_lookup_and_layer() {
+        // AND of two bits, indexed by (a, b) with a as the most significant bit.
+        let and_table =
+            MultilinearExtension::new(vec![Fr::from(0), Fr::from(0), Fr::from(0), Fr::from(1)]);
+        let lookup = GateType::Lookup {
+            table: and_table,
+            input_vars: 2,
+        };
+        let layer = GkrLayer::new(
+            (0..4)
+                .map(|i| Gate::new(lookup.clone(), i, (i + 1) % 4))
+                .collect(),
+            4,
+        );
+        let bits = vec![Fr::from(1), Fr::from(1), Fr::from(0), Fr::from(1)];
+        let circuit = GkrCircuit::new(bits, vec![layer]);
+        // Lookups can be evaluated but not yet proven.
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::UnsupportedGate {
+                layer_idx: 0,
+                gate_idx: 0,
+            })
+        );
+        assert_eq!(
+            circuit.evaluate(),
+            vec![Fr::from(1), Fr::from(0), Fr::from(0), Fr::from(1)]
+        );
+    }
+
+    #[test]
+    fn test_fan_out_circuit() {
+        let circuit = GkrCircuit::fan_out_circuit(input(2), &[(0, vec![0, 2]), (1, vec![1])]);
+        assert_eq!(circuit.check_well_formed(), Ok(()));
+        assert_eq!(
+            circuit.evaluate(),
+            vec![Fr::from(1), Fr::from(2), Fr::from(1), Fr::from(0)]
+        );
+    }
+
+    #[test]
+    fn test_allow_fan_out() {
+        // x^2 feeds into both x^4 and x^2 + 1.
+        let square = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 0),
+                Gate::new(GateType::Add, 1, 1),
+            ],
+            2,
+        );
+        let uses = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 0),
+                Gate::constant(GateType::ConstAdd(Fr::from(1)), 0),
+            ],
+            2,
+        );
+        let x = Fr::from(3);
+        let circuit = GkrCircuit::new(vec![x, Fr::from(0)], vec![square, uses]);
+        assert!(circuit.layers().iter().all(GkrLayer::has_fan_out));
+
+        // The same computation with every intermediate result duplicated by
+        // hand.
+        let square = GkrLayer::new(
+            (0..4).map(|i| Gate::new(GateType::Mul, i, i + 4)).collect(),
+            8,
+        );
+        let uses = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 1),
+                Gate::constant(GateType::ConstAdd(Fr::from(1)), 2),
+            ],
+            4,
+        );
+        let manual = GkrCircuit::new(vec![x; 8], vec![square, uses]);
+        assert!(manual.layers().iter().all(|layer| !layer.has_fan_out()));
+
+        let expanded = circuit.allow_fan_out();
+        assert_eq!(expanded.check_well_formed(), Ok(()));
+        assert_eq!(expanded.depth(), 4);
+        assert!(!expanded.layers()[1].has_fan_out());
+        assert!(!expanded.layers()[3].has_fan_out());
+        assert_eq!(expanded.evaluate()[..2], manual.evaluate()[..]);
+        assert_eq!(circuit.evaluate()[..], manual.evaluate()[..]);
+    }
+
+    #[test]
+    fn test_skip_connections() {
+        // Wire indices are positions in the concatenation of the layers below:
+        // the input at 0..4, the first layer at 4..6 and the second at 6..8.
+        let layers = vec![
+            vec![
+                Gate::new(GateType::Mul, 0, 1),
+                Gate::new(GateType::Mul, 2, 3),
+            ],
+            vec![
+                Gate::new(GateType::Add, 4, 5),
+                Gate::linear_combination(vec![(0, Fr::from(2)), (4, Fr::from(1))]),
+            ],
+            vec![
+                Gate::new(GateType::Mul, 6, 0),
+                Gate::new(GateType::Add, 7, 5),
+            ],
+        ];
+
+        // Un-compiled evaluation, appending each layer's values to the
+        // concatenation.
+        let mut values = input(4);
+        let mut layer_values = vec![];
+        for gates in &layers {
+            let next: Vec<Fr> = gates.iter().map(|gate| gate.evaluate(&values)).collect();
+            values.extend(&next);
+            layer_values.push(next);
+        }
+        assert_eq!(layer_values[2], [14, 16].map(Fr::from));
+
+        let circuit = GkrCircuit::with_skip_connections(input(4), layers);
+        assert_eq!(circuit.check_well_formed(), Ok(()));
+        assert_eq!(circuit.depth(), 3);
+        assert!(circuit
+            .layers()
+            .iter()
+            .map(GkrLayer::num_gates)
+            .eq([4, 4, 2]));
+        let (output, trace) = circuit.evaluate_with_trace();
+        assert_eq!(output, layer_values[2]);
+        for (layer, expected) in trace[1..].iter().zip(&layer_values) {
+            assert_eq!(layer[..expected.len()], expected[..]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_skip_connections_read_above() {
+        GkrCircuit::with_skip_connections(input(2), vec![vec![Gate::new(GateType::Add, 0, 2)]]);
+    }
+
+    #[test]
+    fn test_topological_sort_and_layer() {
+        const NUM_INPUTS: usize = 4;
+        const NUM_GATES: usize = 12;
+        let mut rng = test_rng();
+        let input = input(NUM_INPUTS as u64);
+
+        // A random acyclic graph, each gate reading two values defined before it.
+        let ordered: Vec<(GateType<Fr>, usize, usize)> = (NUM_INPUTS..NUM_INPUTS + NUM_GATES)
+            .map(|id| {
+                let gate_type = if rng.gen() {
+                    GateType::Add
+                } else {
+                    GateType::Mul
+                };
+                (gate_type, rng.gen_range(0..id), rng.gen_range(0..id))
+            })
+            .collect();
+        let mut values = input.clone();
+        for (gate_type, left, right) in &ordered {
+            let value = Gate::new(gate_type.clone(), *left, *right).evaluate(&values);
+            values.push(value);
+        }
+
+        // The same gates under shuffled IDs: gate `i` gets ID NUM_INPUTS + perm[i].
+        let mut perm: Vec<usize> = (0..NUM_GATES).collect();
+        perm.shuffle(&mut rng);
+        let new_id = |id: usize| {
+            if id < NUM_INPUTS {
+                id
+            } else {
+                NUM_INPUTS + perm[id - NUM_INPUTS]
+            }
+        };
+        let mut shuffled = vec![(GateType::Add, 0, 0); NUM_GATES];
+        for (i, (gate_type, left, right)) in ordered.iter().enumerate() {
+            shuffled[perm[i]] = (gate_type.clone(), new_id(*left), new_id(*right));
+        }
+
+        // The output is the unread gates, in the order of their new IDs.
+        let sinks: Vec<usize> = (NUM_INPUTS..NUM_INPUTS + NUM_GATES)
+            .filter(|id| {
+                ordered
+                    .iter()
+                    .all(|(_, left, right)| left != id && right != id)
+            })
+            .collect();
+        let expected_output = |new_id: &dyn Fn(usize) -> usize| {
+            let mut sinks = sinks.clone();
+            sinks.sort_by_key(|&id| new_id(id));
+            let mut output: Vec<Fr> = sinks.iter().map(|&id| values[id]).collect();
+            output.resize(output.len().next_power_of_two(), Fr::ZERO);
+            output
+        };
+
+        for (gates, expected) in [
+            (&ordered, expected_output(&|id| id)),
+            (&shuffled, expected_output(&new_id)),
+        ] {
+            let circuit = GkrCircuit::topological_sort_and_layer(input.clone(), gates).unwrap();
+            assert_eq!(circuit.check_well_formed(), Ok(()));
+            assert_eq!(circuit.evaluate(), expected);
+        }
+    }
+
+    #[test]
+    fn test_topological_sort_errors() {
+        // Gate 2 reads gate 3, which reads gate 2.
+        let cyclic = [(GateType::Add, 2, 0), (GateType::Mul, 2, 1)];
+        assert_eq!(
+            GkrCircuit::topological_sort_and_layer(input(2), &cyclic),
+            Err(GkrError::CyclicWiring { gate_id: 2 })
+        );
+        let out_of_bounds = [(GateType::Add, 0, 3)];
+        assert_eq!(
+            GkrCircuit::topological_sort_and_layer(input(2), &out_of_bounds),
+            Err(GkrError::OutOfBoundsWiring {
+                gate_idx: 2,
+                input_idx: 3,
+                max_idx: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_circuits() {
+        let circuit = GkrCircuit::new(input(8), vec![]);
+        assert_eq!(circuit.check_well_formed(), Err(GkrError::EmptyCircuit));
+
+        let circuit = GkrCircuit::new(input(6), vec![pairwise_mul_layer(3)]);
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::NonPowerOfTwoInputWidth(6))
+        );
+
+        let circuit = GkrCircuit::new(input(4), vec![pairwise_mul_layer(4)]);
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::InputWidthMismatch {
+                input_width: 4,
+                expected: 8,
+            })
+        );
+
+        let circuit = GkrCircuit::new(
+            input(8),
+            vec![
+                pairwise_mul_layer(4),
+                pairwise_mul_layer(2),
+                pairwise_mul_layer(2),
+            ],
+        );
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::LayerWidthMismatch {
+                layer_idx: 2,
+                expected: 4,
+                actual: 2,
+            })
+        );
+
+        let three_gates =
+            GkrLayer::new((0..3).map(|i| Gate::new(GateType::Add, i, i)).collect(), 4);
+        let circuit = GkrCircuit::new(input(4), vec![three_gates]);
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::NonPowerOfTwoLayerWidth {
+                layer_idx: 0,
+                width: 3,
+            })
+        );
+
+        let dangling_wire = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Add, 0, 1),
+                Gate::new(GateType::Mul, 2, 4),
+            ],
+            4,
+        );
+        let circuit = GkrCircuit::new(input(4), vec![dangling_wire]);
+        assert_eq!(
+            circuit.check_well_formed(),
+            Err(GkrError::InvalidWiring {
+                layer_idx: 0,
+                gate_idx: 1,
+                input_idx: 4,
+                num_input_gates: 4,
+            })
+        );
+    }
+}
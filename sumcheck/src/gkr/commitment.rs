@@ -0,0 +1,276 @@
+//! GKR with committed layers: rather than sending \tilde{V_{i - 1}}(r_x) and
+//! \tilde{V_{i - 1}}(r_y) as raw field elements, the prover commits to the
+//! values of every layer read by a proven layer up front and opens each
+//! commitment at the sumcheck's challenges.
+
+use super::{
+    absorb_circuit_description, bottom_layer_values, circuit::GkrCircuit, first_proven_layer,
+    output_layer_claim, prove_layer_with_inputs, verify_layer, GkrError,
+};
+use crate::{mle::MultilinearExtension, utils::SumcheckProof};
+use shared_types::{transcript::TranscriptSponge, Field};
+
+/// A commitment scheme for [MultilinearExtension]s, opened at single points.
+pub trait CommitmentScheme<F: Field> {
+    /// Proof that a committed MLE takes some value at some point.
+    type Opening;
+
+    /// Commits to `mle`. The commitment is a field element so that it can be
+    /// absorbed into the transcript, e.g. the hash of a group element.
+    fn commit(&self, mle: &MultilinearExtension<F>) -> F;
+
+    /// Proves the value of `mle` at `point`.
+    fn open(&self, mle: &MultilinearExtension<F>, point: &[F]) -> Self::Opening;
+
+    /// Returns the value which `opening` proves for the MLE committed to by
+    /// `commitment` at `point`, or `None` if the opening is invalid.
+    fn verify(&self, commitment: F, point: &[F], opening: &Self::Opening) -> Option<F>;
+}
+
+/// Like a [super::GkrLayerProof], but with openings of the committed layer
+/// below in place of the raw evaluations at the sumcheck's challenges.
+pub struct GkrLayerProofWithOpenings<F: Field, O> {
+    sumcheck_proof: SumcheckProof<F>,
+    left_opening: O,
+    right_opening: O,
+}
+
+impl<F: Field, O> GkrLayerProofWithOpenings<F, O> {
+    pub fn get_sumcheck_proof(&self) -> &SumcheckProof<F> {
+        &self.sumcheck_proof
+    }
+
+    pub fn get_left_opening(&self) -> &O {
+        &self.left_opening
+    }
+
+    pub fn get_right_opening(&self) -> &O {
+        &self.right_opening
+    }
+}
+
+/// A GKR proof in which the layers read by proven layers are committed to:
+/// * `commitments` holds one commitment per such layer, from the lowest up.
+/// * `layer_proofs` holds one proof per proven layer, ordered as in a
+///   [super::GkrProof].
+pub struct GkrProofWithCommitments<F: Field, O> {
+    commitments: Vec<F>,
+    layer_proofs: Vec<GkrLayerProofWithOpenings<F, O>>,
+}
+
+impl<F: Field, O> GkrProofWithCommitments<F, O> {
+    pub fn get_commitments(&self) -> &[F] {
+        &self.commitments
+    }
+
+    pub fn get_layer_proofs(&self) -> &[GkrLayerProofWithOpenings<F, O>] {
+        &self.layer_proofs
+    }
+}
+
+/// Proves that `circuit` evaluates to `circuit.evaluate()` on its input, with
+/// the values of each layer below a proven layer committed to via `scheme`.
+/// The commitments are absorbed after the circuit description and before the
+/// output. Returns an error if the circuit cannot be proven, see
+/// [GkrCircuit::check_well_formed].
+pub fn gkr_prove_with_commitment<F: Field, C: CommitmentScheme<F>>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+    scheme: &C,
+) -> Result<GkrProofWithCommitments<F, C::Opening>, GkrError> {
+    circuit.check_well_formed()?;
+    absorb_circuit_description(transcript, circuit);
+
+    let first_proven = first_proven_layer(circuit);
+    let (output, trace) = circuit.evaluate_with_trace();
+    let mles: Vec<MultilinearExtension<F>> = trace[first_proven..circuit.depth()]
+        .iter()
+        .map(|values| MultilinearExtension::new(values.clone()))
+        .collect();
+    let commitments: Vec<F> = mles.iter().map(|mle| scheme.commit(mle)).collect();
+    transcript.absorb_elements(&commitments);
+
+    let mut claim = output_layer_claim(transcript, &output);
+    let mut layer_proofs = Vec::with_capacity(mles.len());
+    for (layer, mle) in circuit.layers()[first_proven..].iter().zip(&mles).rev() {
+        let (layer_proof, next_claim) =
+            prove_layer_with_inputs(transcript, layer, mle.table(), claim, &mut |_| {});
+        let [(_, left_point), (_, right_point)] = &next_claim.weighted_points[..] else {
+            unreachable!("A layer's sumcheck leaves claims at exactly two points")
+        };
+        layer_proofs.push(GkrLayerProofWithOpenings {
+            sumcheck_proof: layer_proof.sumcheck_proof,
+            left_opening: scheme.open(mle, left_point),
+            right_opening: scheme.open(mle, right_point),
+        });
+        claim = next_claim;
+    }
+
+    Ok(GkrProofWithCommitments {
+        commitments,
+        layer_proofs,
+    })
+}
+
+/// Verifies a [GkrProofWithCommitments] that `circuit` evaluates to `output`
+/// on its input. Besides the checks of [super::gkr_verify], the commitment to
+/// the lowest layer must match the values the verifier knows for it.
+pub fn gkr_verify_with_commitment<F: Field, C: CommitmentScheme<F>>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+    output: &[F],
+    proof: &GkrProofWithCommitments<F, C::Opening>,
+    scheme: &C,
+) -> bool {
+    if circuit.check_well_formed().is_err() {
+        return false;
+    }
+
+    let first_proven = first_proven_layer(circuit);
+    let num_proven = circuit.depth() - first_proven;
+    if output.len() != circuit.layers().last().unwrap().num_gates()
+        || proof.commitments.len() != num_proven
+        || proof.layer_proofs.len() != num_proven
+    {
+        return false;
+    }
+
+    absorb_circuit_description(transcript, circuit);
+    transcript.absorb_elements(&proof.commitments);
+    let mut claim = output_layer_claim(transcript, output);
+    for ((layer, layer_proof), &commitment) in circuit
+        .layers()
+        .iter()
+        .skip(first_proven)
+        .zip(proof.layer_proofs.iter().rev())
+        .zip(&proof.commitments)
+        .rev()
+    {
+        let evals = |left_point: &[F], right_point: &[F]| {
+            Some((
+                scheme.verify(commitment, left_point, &layer_proof.left_opening)?,
+                scheme.verify(commitment, right_point, &layer_proof.right_opening)?,
+            ))
+        };
+        match verify_layer(
+            transcript,
+            layer,
+            &claim,
+            &layer_proof.sumcheck_proof,
+            evals,
+        ) {
+            Some(next_claim) => claim = next_claim,
+            None => return false,
+        }
+    }
+
+    // Without any proven layer, nothing is committed to.
+    let bottom_values = bottom_layer_values(circuit, first_proven);
+    let bottom_mle = MultilinearExtension::new(bottom_values.clone());
+    let bottom_commitment = proof.commitments.first();
+    if bottom_commitment.is_some_and(|&commitment| commitment != scheme.commit(&bottom_mle))
+        || !claim.holds_for(&bottom_values)
+    {
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gkr_prove_with_commitment, gkr_verify_with_commitment, CommitmentScheme};
+    use crate::{
+        gkr::circuit::{random_circuit, GkrCircuitBuilder},
+        mle::MultilinearExtension,
+    };
+    use ark_std::test_rng;
+    use shared_types::{ff_field, transcript::poseidon_sponge::PoseidonSponge, Field, Fr};
+
+    /// Pedersen-like mock commitment \sum_b g_b * f(b) to the table of f, with
+    /// the generators g_b taken from the field rather than a group. Openings
+    /// reveal the whole table, which the verifier recommits to and evaluates.
+    struct MockPedersen<F> {
+        generators: Vec<F>,
+    }
+
+    impl<F: Field> CommitmentScheme<F> for MockPedersen<F> {
+        type Opening = MultilinearExtension<F>;
+
+        fn commit(&self, mle: &MultilinearExtension<F>) -> F {
+            assert!(mle.table().len() <= self.generators.len());
+            mle.table()
+                .iter()
+                .zip(&self.generators)
+                .fold(F::ZERO, |acc, (value, generator)| acc + *value * generator)
+        }
+
+        fn open(&self, mle: &MultilinearExtension<F>, _point: &[F]) -> Self::Opening {
+            mle.clone()
+        }
+
+        fn verify(&self, commitment: F, point: &[F], opening: &Self::Opening) -> Option<F> {
+            (opening.num_vars() == point.len() && self.commit(opening) == commitment)
+                .then(|| opening.evaluate(point))
+        }
+    }
+
+    fn mock_pedersen(max_len: usize) -> MockPedersen<Fr> {
+        let mut rng = test_rng();
+        MockPedersen {
+            generators: (0..max_len).map(|_| Fr::random(&mut rng)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_gkr_with_commitment() {
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer((1..=8).map(Fr::from).collect())
+            .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+            .add_add_layer(vec![(0, 1), (2, 3)])
+            .add_mul_layer(vec![(0, 1), (1, 0)])
+            .build()
+            .unwrap();
+        let output = circuit.evaluate();
+        let scheme = mock_pedersen(8);
+
+        let proof =
+            gkr_prove_with_commitment(&mut PoseidonSponge::default(), &circuit, &scheme).unwrap();
+        assert_eq!(proof.get_commitments().len(), circuit.depth());
+        assert!(gkr_verify_with_commitment(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof,
+            &scheme
+        ));
+
+        let mut wrong_output = output;
+        wrong_output[0] += Fr::ONE;
+        assert!(!gkr_verify_with_commitment(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &wrong_output,
+            &proof,
+            &scheme
+        ));
+    }
+
+    #[test]
+    fn test_gkr_with_commitment_random_circuits() {
+        let mut rng = test_rng();
+        let scheme = mock_pedersen(8);
+        for _ in 0..5 {
+            let circuit = random_circuit::<Fr>(3, 3, &mut rng);
+            let proof =
+                gkr_prove_with_commitment(&mut PoseidonSponge::default(), &circuit, &scheme)
+                    .unwrap();
+            assert!(gkr_verify_with_commitment(
+                &mut PoseidonSponge::default(),
+                &circuit,
+                &circuit.evaluate(),
+                &proof,
+                &scheme
+            ));
+        }
+    }
+}
@@ -0,0 +1,1206 @@
+//! The GKR protocol, which proves the output of a layered arithmetic circuit by
+//! reducing a claim about each layer's values to a claim about the values of
+//! the layer below it, one sumcheck per layer, until a claim about the input
+//! layer remains which the verifier checks directly.
+//!
+//! For a layer whose values are V_i and whose inputs are V_{i - 1}, a claim
+//! \sum_j c_j * \tilde{V_i}(z_j) = v is reduced via the sumcheck over
+//! (x, y) \in \{0, 1}^{2s} of
+//! add(x, y) * (V_{i - 1}(x) + V_{i - 1}(y)) + mul(x, y) * V_{i - 1}(x) * V_{i - 1}(y),
+//! where add(x, y) = \sum_j c_j * \sum_{g \in add gates} eq(z_j, g) * [x = left(g)] * [y = right(g)]
+//! (and similarly for mul). Constant gates, which read only x, contribute
+//! lin(x, y) * V_{i - 1}(x) + offset(x, y), where lin and offset are supported
+//! on y = 0 and weigh each gate by its constant (for lin, only for ConstMul
+//! gates) in the same way. This leaves the verifier needing
+//! \tilde{V_{i - 1}}(r_x) and \tilde{V_{i - 1}}(r_y), which the prover sends
+//! and which are folded into the next claim with fresh random coefficients.
+//!
+//! Layers below a constant layer (see [GkrLayer::is_constant_layer]) do not
+//! affect the output, so the reduction stops at the topmost constant layer,
+//! whose values the verifier computes itself to check the remaining claim.
+//!
+//! See [commitment] for a variant in which the prover commits to the layers
+//! and opens the commitments instead of sending raw evaluations.
+
+pub mod circuit;
+pub mod commitment;
+
+use crate::{
+    mle::MultilinearExtension,
+    sumcheck::{replay_transcript, SumcheckProver},
+    utils::{SumcheckProof, UnivariateEvals},
+};
+use ark_std::log2;
+use circuit::{Gate, GateType, GkrCircuit, GkrLayer};
+use shared_types::{
+    transcript::{fiat_shamir::FiatShamir, TranscriptSponge},
+    Field,
+};
+use thiserror::Error;
+
+/// Reasons for which a [GkrCircuit] cannot be proven.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GkrError {
+    #[error("Circuit has no layers above the input layer")]
+    EmptyCircuit,
+    #[error("Input layer has {0} values, which is not a power of two")]
+    NonPowerOfTwoInputWidth(usize),
+    #[error("Layer {layer_idx} has {width} gates, which is not a power of two")]
+    NonPowerOfTwoLayerWidth { layer_idx: usize, width: usize },
+    #[error("Input layer has {input_width} values but the first layer expects {expected}")]
+    InputWidthMismatch { input_width: usize, expected: usize },
+    #[error(
+        "Layer {layer_idx} expects {expected} inputs but the layer below it has {actual} gates"
+    )]
+    LayerWidthMismatch {
+        layer_idx: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Gate {gate_idx} of layer {layer_idx} reads input {input_idx}, but the layer below it only has {num_input_gates} gates")]
+    InvalidWiring {
+        layer_idx: usize,
+        gate_idx: usize,
+        input_idx: usize,
+        num_input_gates: usize,
+    },
+    #[error("Gate {gate_idx} reads input {input_idx}, but inputs must be less than {max_idx}")]
+    OutOfBoundsWiring {
+        gate_idx: usize,
+        input_idx: usize,
+        max_idx: usize,
+    },
+    #[error("Gate {gate_id} is on or reads from a cycle of gates")]
+    CyclicWiring { gate_id: usize },
+    #[error("Gate {gate_idx} of layer {layer_idx} is of a type GKR cannot prove yet")]
+    UnsupportedGate { layer_idx: usize, gate_idx: usize },
+}
+
+/// A stage of [gkr_prove_with_progress], reported to its [ProgressCallback].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GkrProgressEvent {
+    /// The prover started on layer `layer_idx` of a circuit with
+    /// `total_layers` layers above the input layer.
+    LayerStarted {
+        layer_idx: usize,
+        total_layers: usize,
+    },
+    /// The prover finished round `round` of the sumcheck for layer
+    /// `layer_idx`.
+    SumcheckRoundCompleted { layer_idx: usize, round: usize },
+    /// The prover finished the whole proof.
+    ProofCompleted,
+}
+
+/// Receives the [GkrProgressEvent]s of a proof, e.g. to report them to a UI or
+/// a log.
+pub type ProgressCallback = Box<dyn Fn(GkrProgressEvent) + Send>;
+
+/// The prover's messages for reducing a claim on one layer to a claim on the
+/// layer below it:
+/// * The sumcheck proof over (x, y) described in the module documentation.
+/// * The evaluations \tilde{V_{i - 1}}(r_x) and \tilde{V_{i - 1}}(r_y) at the
+///   sumcheck's challenges.
+pub struct GkrLayerProof<F: Field> {
+    sumcheck_proof: SumcheckProof<F>,
+    left_eval: F,
+    right_eval: F,
+}
+
+impl<F: Field> GkrLayerProof<F> {
+    pub fn get_sumcheck_proof(&self) -> &SumcheckProof<F> {
+        &self.sumcheck_proof
+    }
+
+    pub fn get_left_eval(&self) -> F {
+        self.left_eval
+    }
+
+    pub fn get_right_eval(&self) -> F {
+        self.right_eval
+    }
+}
+
+/// A GKR proof consists of one [GkrLayerProof] per circuit layer, ordered from
+/// the output layer down to the layer directly above the input, or down to the
+/// layer directly above the topmost constant layer if there is one.
+pub struct GkrProof<F: Field> {
+    layer_proofs: Vec<GkrLayerProof<F>>,
+}
+
+impl<F: Field> GkrProof<F> {
+    pub fn get_layer_proofs(&self) -> &[GkrLayerProof<F>] {
+        &self.layer_proofs
+    }
+}
+
+/// A claim that \sum_j c_j * \tilde{V}(z_j) = `value` for a layer's values V,
+/// where `weighted_points` holds the pairs (c_j, z_j).
+pub struct LayerClaim<F> {
+    weighted_points: Vec<(F, Vec<F>)>,
+    value: F,
+}
+
+impl<F: Field> LayerClaim<F> {
+    /// w(g) = \sum_j c_j * eq(z_j, g) for every g in \{0, 1}^{num_vars}.
+    fn gate_weights(&self, num_vars: usize) -> Vec<F> {
+        let mut weights = vec![F::ZERO; 1 << num_vars];
+        for (coeff, point) in &self.weighted_points {
+            let eq_poly = MultilinearExtension::eq_poly(point);
+            for (weight, eq_eval) in weights.iter_mut().zip(eq_poly.table()) {
+                *weight += *coeff * eq_eval;
+            }
+        }
+        weights
+    }
+
+    /// Checks the claim directly against the values of its layer.
+    pub fn holds_for(&self, values: &[F]) -> bool {
+        let combined = self
+            .weighted_points
+            .iter()
+            .fold(F::ZERO, |acc, (coeff, point)| {
+                acc + *coeff * MultilinearExtension::evaluate_via_folding(values.to_vec(), point)
+            });
+        combined == self.value
+    }
+}
+
+/// The weighted wiring predicates add, mul, lin and offset of a layer, either
+/// as tables over (x, y) or as evaluations at a single point.
+struct WeightedWiring<T> {
+    add: T,
+    mul: T,
+    lin: T,
+    offset: T,
+}
+
+impl<F: Field> WeightedWiring<F> {
+    /// Evaluates the weighted wiring predicates of `layer` at (`left_point`,
+    /// `right_point`), given the gate weights w(g) of the claim being reduced.
+    fn evaluate(layer: &GkrLayer<F>, weights: &[F], left_point: &[F], right_point: &[F]) -> Self {
+        let left_eq = MultilinearExtension::eq_poly(left_point);
+        let right_eq = MultilinearExtension::eq_poly(right_point);
+        let (left_eq, right_eq) = (left_eq.table(), right_eq.table());
+        let mut evals = WeightedWiring {
+            add: F::ZERO,
+            mul: F::ZERO,
+            lin: F::ZERO,
+            offset: F::ZERO,
+        };
+        for (gate, weight) in layer.gates().iter().zip(weights) {
+            let term = *weight * left_eq[gate.left];
+            match &gate.gate_type {
+                GateType::Add => evals.add += term * right_eq[gate.right],
+                GateType::Mul => evals.mul += term * right_eq[gate.right],
+                GateType::ConstAdd(constant) => {
+                    evals.lin += term * right_eq[0];
+                    evals.offset += term * right_eq[0] * constant;
+                }
+                GateType::ConstMul(constant) => evals.lin += term * right_eq[0] * constant,
+                GateType::Lookup { .. } => {
+                    unreachable!("Lookup gates are rejected by GkrCircuit::check_well_formed")
+                }
+                GateType::LinearCombination(terms) => {
+                    for (input_idx, coeff) in terms {
+                        evals.lin += *weight * left_eq[*input_idx] * right_eq[0] * coeff;
+                    }
+                }
+            }
+        }
+        evals
+    }
+
+    /// The layer's summand at (r_x, r_y), given V(r_x) and V(r_y).
+    fn combine(&self, left_eval: F, right_eval: F) -> F {
+        self.add * (left_eval + right_eval)
+            + self.mul * left_eval * right_eval
+            + self.lin * left_eval
+            + self.offset
+    }
+}
+
+impl<F: Field> WeightedWiring<Vec<F>> {
+    /// Tables of the weighted wiring predicates of `layer` over (x, y), given
+    /// the gate weights w(g) of the claim being reduced.
+    fn tables(layer: &GkrLayer<F>, weights: Vec<F>) -> Self {
+        let num_input_vars = log2(layer.num_input_gates()) as usize;
+        let zero_table = vec![F::ZERO; 1 << (2 * num_input_vars)];
+        let mut tables = WeightedWiring {
+            add: zero_table.clone(),
+            mul: zero_table.clone(),
+            lin: zero_table.clone(),
+            offset: zero_table,
+        };
+        for (gate, weight) in layer.gates().iter().zip(weights) {
+            let idx = (gate.left << num_input_vars) + gate.right;
+            // Constant gates read x only, and sit at y = 0.
+            let const_idx = gate.left << num_input_vars;
+            match &gate.gate_type {
+                GateType::Add => tables.add[idx] += weight,
+                GateType::Mul => tables.mul[idx] += weight,
+                GateType::ConstAdd(constant) => {
+                    tables.lin[const_idx] += weight;
+                    tables.offset[const_idx] += weight * constant;
+                }
+                GateType::ConstMul(constant) => tables.lin[const_idx] += weight * constant,
+                GateType::Lookup { .. } => {
+                    unreachable!("Lookup gates are rejected by GkrCircuit::check_well_formed")
+                }
+                // Like constant gates, but spread over every x the gate reads.
+                GateType::LinearCombination(terms) => {
+                    for (input_idx, coeff) in terms {
+                        tables.lin[input_idx << num_input_vars] += weight * coeff;
+                    }
+                }
+            }
+        }
+        tables
+    }
+}
+
+/// Index of the first layer which is proven, i.e. the one above the topmost
+/// constant layer, or zero if no layer is constant.
+fn first_proven_layer<F: Field>(circuit: &GkrCircuit<F>) -> usize {
+    circuit
+        .layers()
+        .iter()
+        .rposition(|layer| layer.is_constant_layer())
+        .map_or(0, |idx| idx + 1)
+}
+
+/// Absorbs the prover's evaluations at the end of a layer's sumcheck and
+/// combines them into a single claim on the layer below.
+fn next_layer_claim<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    left_point: &[F],
+    right_point: &[F],
+    left_eval: F,
+    right_eval: F,
+) -> LayerClaim<F> {
+    transcript.absorb_elements(&[left_eval, right_eval]);
+    let left_coeff = transcript.squeeze();
+    let right_coeff = transcript.squeeze();
+    LayerClaim {
+        weighted_points: vec![
+            (left_coeff, left_point.to_vec()),
+            (right_coeff, right_point.to_vec()),
+        ],
+        value: left_coeff * left_eval + right_coeff * right_eval,
+    }
+}
+
+/// Absorbs the circuit output and samples the point at which the verifier
+/// checks the output layer's multilinear extension.
+pub fn output_layer_claim<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    output: &[F],
+) -> LayerClaim<F> {
+    transcript.absorb_elements(output);
+    let point = transcript.squeeze_elements(log2(output.len()) as usize);
+    let value = MultilinearExtension::evaluate_via_folding(output.to_vec(), &point);
+    LayerClaim {
+        weighted_points: vec![(F::ONE, point)],
+        value,
+    }
+}
+
+/// Binds the transcript to the circuit being proven by absorbing its depth,
+/// the width of every layer, the index and value of every public input and
+/// every layer's list of gates (see [gate_description]), which takes time
+/// linear in the circuit's size. Witness input values are not absorbed.
+pub fn absorb_circuit_description<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+) {
+    transcript.absorb(F::from(circuit.depth() as u64));
+    transcript.absorb(F::from(circuit.input().len() as u64));
+    let public_inputs = circuit.public_inputs();
+    transcript.absorb(F::from(public_inputs.len() as u64));
+    for (idx, value) in public_inputs {
+        transcript.absorb_elements(&[F::from(idx as u64), value]);
+    }
+    for layer in circuit.layers() {
+        transcript.absorb(F::from(layer.num_gates() as u64));
+        for gate in layer.gates() {
+            transcript.absorb_elements(&gate_description(gate));
+        }
+    }
+}
+
+/// The elements identifying `gate`: a tag for its type, its `left` and `right`
+/// inputs, then its constant, its (input index, coefficient) terms prefixed by
+/// their number, or its lookup table prefixed by the number of input bits.
+fn gate_description<F: Field>(gate: &Gate<F>) -> Vec<F> {
+    let (tag, extra) = match &gate.gate_type {
+        GateType::Add => (0, vec![]),
+        GateType::Mul => (1, vec![]),
+        GateType::ConstAdd(constant) => (2, vec![*constant]),
+        GateType::ConstMul(constant) => (3, vec![*constant]),
+        GateType::Lookup { table, input_vars } => (
+            4,
+            [
+                F::from(*input_vars as u64),
+                F::from(table.table().len() as u64),
+            ]
+            .into_iter()
+            .chain(table.table().iter().copied())
+            .collect(),
+        ),
+        GateType::LinearCombination(terms) => (
+            5,
+            std::iter::once(F::from(terms.len() as u64))
+                .chain(
+                    terms
+                        .iter()
+                        .flat_map(|(idx, coeff)| [F::from(*idx as u64), *coeff]),
+                )
+                .collect(),
+        ),
+    };
+    [
+        F::from(tag),
+        F::from(gate.left as u64),
+        F::from(gate.right as u64),
+    ]
+    .into_iter()
+    .chain(extra)
+    .collect()
+}
+
+/// Sumcheck prover for a sum of products of MLEs, \sum_t \prod_k f_{t, k},
+/// whose round polynomials are the sums of each product's round polynomial.
+/// Calls `on_round` with the index of each round once it is done. Returns the
+/// proof along with the challenges r_1, ..., r_n and the final state of each
+/// term's prover, whose factors are all bound to the challenges.
+fn prove_sum_of_products<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    terms: &[Vec<MultilinearExtension<F>>],
+    claimed_sum: F,
+    on_round: &mut impl FnMut(usize),
+) -> (SumcheckProof<F>, Vec<F>, Vec<SumcheckProver<F>>) {
+    let n = terms
+        .iter()
+        .flatten()
+        .map(|f| f.num_vars())
+        .max()
+        .unwrap_or(0);
+    let mut fiat_shamir = FiatShamir::new(transcript);
+    fiat_shamir.absorb_prover_message(&[claimed_sum]);
+
+    let mut provers: Vec<SumcheckProver<F>> =
+        terms.iter().map(|term| SumcheckProver::new(term)).collect();
+    let mut prover_msgs = Vec::with_capacity(n);
+    let mut challenges = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let term_evals: Vec<UnivariateEvals<F>> = provers
+            .iter()
+            .map(|prover| UnivariateEvals::new(prover.round_evals(n - i - 1)))
+            .collect();
+
+        // Terms may have different degrees; extend each to the largest one.
+        let degree = term_evals.iter().map(|e| e.get_degree()).max().unwrap();
+        let evals: Vec<F> = (0..=degree)
+            .map(|x| {
+                term_evals.iter().fold(F::ZERO, |acc, term| {
+                    acc + term.evaluate_at_a_point(F::from(x as u64)).unwrap()
+                })
+            })
+            .collect();
+        fiat_shamir.absorb_prover_message(&evals);
+        prover_msgs.push(UnivariateEvals::new(evals));
+
+        let r_i = fiat_shamir.get_verifier_challenge();
+        provers.iter_mut().for_each(|prover| prover.fold(r_i));
+        challenges.push(r_i);
+        on_round(i);
+    }
+
+    // The verifier's oracle query is the sum of the terms' final evaluations.
+    debug_assert!(prover_msgs.last().is_none_or(|msg| {
+        msg.evaluate_at_a_point(challenges[n - 1]).unwrap()
+            == provers
+                .iter()
+                .fold(F::ZERO, |acc, prover| acc + prover.final_evaluation())
+    }));
+    (
+        SumcheckProof::new(claimed_sum, prover_msgs),
+        challenges,
+        provers,
+    )
+}
+
+/// Reduces `claim` on the values of layer `layer_idx` of `circuit` to a claim
+/// on the values of the layer below it, which is returned along with the
+/// prover's messages for this layer. Proving layer by layer from the output
+/// layer's claim down reproduces [gkr_prove], so long as the transcript first
+/// absorbs the circuit via [absorb_circuit_description] and the output via
+/// [output_layer_claim].
+///
+/// Evaluates the circuit up to the layer below `layer_idx` from scratch; see
+/// [gkr_prove] for proving every layer from a single evaluation. Returns an
+/// error if the circuit cannot be proven, see [GkrCircuit::check_well_formed].
+pub fn prove_layer<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    layer_idx: usize,
+    claim: LayerClaim<F>,
+    circuit: &GkrCircuit<F>,
+) -> Result<(GkrLayerProof<F>, LayerClaim<F>), GkrError> {
+    circuit.check_well_formed()?;
+    let inputs = circuit.layers()[..layer_idx]
+        .iter()
+        .fold(circuit.input().to_vec(), |values, layer| {
+            layer.evaluate(&values)
+        });
+    Ok(prove_layer_with_inputs(
+        transcript,
+        &circuit.layers()[layer_idx],
+        &inputs,
+        claim,
+        &mut |_| {},
+    ))
+}
+
+/// [prove_layer], given the values `inputs` of the layer below `layer`. Calls
+/// `on_round` after each round of the layer's sumcheck.
+fn prove_layer_with_inputs<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    layer: &GkrLayer<F>,
+    inputs: &[F],
+    claim: LayerClaim<F>,
+    on_round: &mut impl FnMut(usize),
+) -> (GkrLayerProof<F>, LayerClaim<F>) {
+    let num_input_vars = log2(inputs.len()) as usize;
+    let input_mask = (1 << num_input_vars) - 1;
+
+    // Wiring predicates over (x, y), weighted by the current claim.
+    let weights = claim.gate_weights(log2(layer.num_gates()) as usize);
+    let tables = WeightedWiring::tables(layer, weights);
+    let table_len = tables.add.len();
+
+    // V(x) only depends on the first `num_input_vars` variables; V(y) and
+    // V(x) + V(y) are spelled out over all of (x, y).
+    let left_mle = MultilinearExtension::new(inputs.to_vec());
+    let right_mle =
+        MultilinearExtension::new((0..table_len).map(|idx| inputs[idx & input_mask]).collect());
+    let sum_mle = MultilinearExtension::new(
+        (0..table_len)
+            .map(|idx| inputs[idx >> num_input_vars] + inputs[idx & input_mask])
+            .collect(),
+    );
+    let terms = [
+        vec![MultilinearExtension::new(tables.add), sum_mle],
+        vec![
+            MultilinearExtension::new(tables.mul),
+            left_mle.clone(),
+            right_mle,
+        ],
+        vec![MultilinearExtension::new(tables.lin), left_mle],
+        vec![MultilinearExtension::new(tables.offset)],
+    ];
+
+    let (sumcheck_proof, challenges, provers) =
+        prove_sum_of_products(transcript, &terms, claim.value, on_round);
+    let (left_point, right_point) = challenges.split_at(num_input_vars);
+    // The mul term's V(x) and V(y) factors are now bound to the challenges.
+    let mul_factor_evals = provers[1].final_factor_evals();
+    let (left_eval, right_eval) = (mul_factor_evals[1], mul_factor_evals[2]);
+
+    let next_claim = next_layer_claim(transcript, left_point, right_point, left_eval, right_eval);
+    let layer_proof = GkrLayerProof {
+        sumcheck_proof,
+        left_eval,
+        right_eval,
+    };
+    (layer_proof, next_claim)
+}
+
+/// Proves that `circuit` evaluates to `circuit.evaluate()` on its input.
+/// Returns an error if the circuit cannot be proven, see
+/// [GkrCircuit::check_well_formed].
+pub fn gkr_prove<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+) -> Result<GkrProof<F>, GkrError> {
+    gkr_prove_with_progress(transcript, circuit, None)
+}
+
+/// Like [gkr_prove], but reports its progress to `progress`, if given: a
+/// [GkrProgressEvent::LayerStarted] as each proven layer is started, from the
+/// output layer down, a [GkrProgressEvent::SumcheckRoundCompleted] after each
+/// round of its sumcheck, and a [GkrProgressEvent::ProofCompleted] at the end.
+pub fn gkr_prove_with_progress<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+    progress: Option<ProgressCallback>,
+) -> Result<GkrProof<F>, GkrError> {
+    circuit.check_well_formed()?;
+    let report = |event| {
+        if let Some(progress) = &progress {
+            progress(event);
+        }
+    };
+    absorb_circuit_description(transcript, circuit);
+
+    let (output, trace) = circuit.evaluate_with_trace();
+    let mut claim = output_layer_claim(transcript, &output);
+    let first_proven = first_proven_layer(circuit);
+    let mut layer_proofs = Vec::with_capacity(circuit.depth() - first_proven);
+
+    for ((layer_idx, layer), inputs) in circuit
+        .layers()
+        .iter()
+        .enumerate()
+        .skip(first_proven)
+        .zip(&trace[first_proven..])
+        .rev()
+    {
+        report(GkrProgressEvent::LayerStarted {
+            layer_idx,
+            total_layers: circuit.depth(),
+        });
+        let mut on_round =
+            |round| report(GkrProgressEvent::SumcheckRoundCompleted { layer_idx, round });
+        let (layer_proof, next_claim) =
+            prove_layer_with_inputs(transcript, layer, inputs, claim, &mut on_round);
+        layer_proofs.push(layer_proof);
+        claim = next_claim;
+    }
+
+    report(GkrProgressEvent::ProofCompleted);
+    Ok(GkrProof { layer_proofs })
+}
+
+/// Verifies a [GkrProof] that `circuit` evaluates to `output` on its input.
+/// Rejects circuits which cannot be proven, see [GkrCircuit::check_well_formed].
+pub fn gkr_verify<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    circuit: &GkrCircuit<F>,
+    output: &[F],
+    proof: &GkrProof<F>,
+) -> bool {
+    if circuit.check_well_formed().is_err() {
+        return false;
+    }
+
+    let first_proven = first_proven_layer(circuit);
+    if output.len() != circuit.layers().last().unwrap().num_gates()
+        || proof.layer_proofs.len() != circuit.depth() - first_proven
+    {
+        return false;
+    }
+
+    absorb_circuit_description(transcript, circuit);
+    if !check_output_claim(&mut transcript.clone(), proof, output) {
+        return false;
+    }
+    let mut claim = output_layer_claim(transcript, output);
+    for (layer, layer_proof) in circuit
+        .layers()
+        .iter()
+        .skip(first_proven)
+        .zip(proof.layer_proofs.iter().rev())
+        .rev()
+    {
+        let evals = |_: &[F], _: &[F]| Some((layer_proof.left_eval, layer_proof.right_eval));
+        match verify_layer(
+            transcript,
+            layer,
+            &claim,
+            &layer_proof.sumcheck_proof,
+            evals,
+        ) {
+            Some(next_claim) => claim = next_claim,
+            None => return false,
+        }
+    }
+
+    if !claim.holds_for(&bottom_layer_values(circuit, first_proven)) {
+        return false;
+    }
+    true
+}
+
+/// Checks that `claimed_output` is the output `proof` was made for: evaluates
+/// the output layer's MLE at the verifier's first challenge point and compares
+/// it with the claimed sum of the output layer's sumcheck. `transcript` must
+/// have absorbed the circuit via [absorb_circuit_description], as in
+/// [gkr_verify]. A proof without any layer proofs makes no claim about the
+/// output, which is then checked against the constant layer instead.
+pub fn check_output_claim<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    proof: &GkrProof<F>,
+    claimed_output: &[F],
+) -> bool {
+    let claim = output_layer_claim(transcript, claimed_output);
+    proof
+        .layer_proofs
+        .first()
+        .is_none_or(|layer_proof| layer_proof.sumcheck_proof.get_claimed_sum() == claim.value)
+}
+
+/// Checks the sumcheck proof reducing `claim` on `layer` and returns the
+/// resulting claim on the layer below it.
+/// \tilde{V_{i - 1}}(r_x) and \tilde{V_{i - 1}}(r_y) are taken from `evals`,
+/// given (r_x, r_y), which returns `None` if it cannot supply them.
+fn verify_layer<F: Field>(
+    transcript: &mut impl TranscriptSponge<F>,
+    layer: &GkrLayer<F>,
+    claim: &LayerClaim<F>,
+    sumcheck_proof: &SumcheckProof<F>,
+    evals: impl FnOnce(&[F], &[F]) -> Option<(F, F)>,
+) -> Option<LayerClaim<F>> {
+    let num_input_vars = log2(layer.num_input_gates()) as usize;
+    if sumcheck_proof.get_claimed_sum() != claim.value
+        || sumcheck_proof.rounds().len() != 2 * num_input_vars
+    {
+        return None;
+    }
+
+    let (challenges, expected_evaluation) = replay_transcript(transcript, sumcheck_proof).ok()?;
+    let (left_point, right_point) = challenges.split_at(num_input_vars);
+    let weights = claim.gate_weights(log2(layer.num_gates()) as usize);
+    let wiring = WeightedWiring::evaluate(layer, &weights, left_point, right_point);
+
+    let (left_eval, right_eval) = evals(left_point, right_point)?;
+    if wiring.combine(left_eval, right_eval) != expected_evaluation {
+        return None;
+    }
+
+    Some(next_layer_claim(
+        transcript,
+        left_point,
+        right_point,
+        left_eval,
+        right_eval,
+    ))
+}
+
+/// The values of the layer below the lowest proven layer, which the verifier
+/// knows: the input, or the values of the topmost constant layer, which are the
+/// same for any input.
+fn bottom_layer_values<F: Field>(circuit: &GkrCircuit<F>, first_proven: usize) -> Vec<F> {
+    match first_proven {
+        0 => circuit.input().to_vec(),
+        idx => {
+            let constant_layer = &circuit.layers()[idx - 1];
+            constant_layer.evaluate(&vec![F::ZERO; constant_layer.num_input_gates()])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gkr::{
+        absorb_circuit_description, check_output_claim,
+        circuit::{
+            random_circuit, Gate, GateType, GkrCircuit, GkrCircuitBuilder, GkrLayer, InputType,
+        },
+        gkr_prove, gkr_prove_with_progress, gkr_verify, output_layer_claim, prove_layer, GkrError,
+        GkrProgressEvent, GkrProof, ProgressCallback,
+    };
+    use crate::mle::MultilinearExtension;
+    use ark_std::{rand::Rng, test_rng};
+    use shared_types::{
+        ff_field,
+        transcript::{poseidon_sponge::PoseidonSponge, TranscriptSponge},
+        Fr,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Computes ((a * b) + (c * d)) * ((e * f) + (g * h)), duplicated across
+    /// both output gates.
+    fn small_circuit() -> GkrCircuit<Fr> {
+        GkrCircuitBuilder::new()
+            .add_input_layer((1..=8).map(Fr::from).collect())
+            .add_mul_layer(vec![(0, 1), (2, 3), (4, 5), (6, 7)])
+            .add_add_layer(vec![(0, 1), (2, 3)])
+            .add_mul_layer(vec![(0, 1), (1, 0)])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_gkr_completeness() {
+        let circuit = small_circuit();
+        let output = circuit.evaluate();
+        assert_eq!(output, vec![Fr::from(14 * 86); 2]);
+
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_progress_events() {
+        let circuit = small_circuit();
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&events);
+        let progress: ProgressCallback =
+            Box::new(move |event| recorded.lock().unwrap().push(event));
+
+        let proof =
+            gkr_prove_with_progress(&mut PoseidonSponge::default(), &circuit, Some(progress))
+                .unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &circuit.evaluate(),
+            &proof
+        ));
+
+        let mut expected = vec![];
+        for (layer_idx, layer) in circuit.layers().iter().enumerate().rev() {
+            expected.push(GkrProgressEvent::LayerStarted {
+                layer_idx,
+                total_layers: 3,
+            });
+            let num_rounds = 2 * layer.num_input_gates().ilog2() as usize;
+            expected.extend(
+                (0..num_rounds)
+                    .map(|round| GkrProgressEvent::SumcheckRoundCompleted { layer_idx, round }),
+            );
+        }
+        expected.push(GkrProgressEvent::ProofCompleted);
+        assert_eq!(*events.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_gkr_random_circuits() {
+        let mut rng = test_rng();
+        for _ in 0..50 {
+            let depth = rng.gen_range(1..=4);
+            let log_width = rng.gen_range(0..=3);
+            let circuit = random_circuit::<Fr>(depth, log_width, &mut rng);
+            assert_eq!(circuit.check_well_formed(), Ok(()));
+
+            let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+            assert!(gkr_verify(
+                &mut PoseidonSponge::default(),
+                &circuit,
+                &circuit.evaluate(),
+                &proof
+            ));
+        }
+    }
+
+    #[test]
+    fn test_gkr_constant_layer() {
+        // A multiplication layer, a constant layer of zeros, a layer adding
+        // constants to the zeros and a final multiplication layer.
+        let mul_layer = |num_gates: usize| {
+            let gates = (0..num_gates)
+                .map(|i| Gate::new(GateType::Mul, 2 * i, 2 * i + 1))
+                .collect();
+            GkrLayer::new(gates, 2 * num_gates)
+        };
+        let zero_layer = GkrLayer::new(
+            (0..4)
+                .map(|i| Gate::constant(GateType::ConstMul(Fr::ZERO), i))
+                .collect(),
+            4,
+        );
+        let bias_layer = GkrLayer::new(
+            (0..4)
+                .map(|i| Gate::constant(GateType::ConstAdd(Fr::from(i as u64 + 2)), i))
+                .collect(),
+            4,
+        );
+        let input = (1..=8).map(Fr::from).collect();
+        let circuit = GkrCircuit::new(
+            input,
+            vec![mul_layer(4), zero_layer, bias_layer, mul_layer(2)],
+        );
+        assert!(circuit.layers()[1].is_constant_layer());
+        assert!(!circuit.layers()[2].is_constant_layer());
+
+        let output = circuit.evaluate();
+        assert_eq!(output, vec![Fr::from(6), Fr::from(20)]);
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert_eq!(proof.get_layer_proofs().len(), 2);
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+
+        let wrong_output = vec![Fr::from(6), Fr::from(21)];
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &wrong_output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_prove_layer_by_layer() {
+        let circuit = small_circuit();
+        let (output, trace) = circuit.evaluate_with_trace();
+
+        // The witness MLE of every layer, built from the trace.
+        let witnesses: Vec<_> = trace
+            .iter()
+            .map(|values| MultilinearExtension::new(values.clone()))
+            .collect();
+        assert_eq!(witnesses[1].table(), &[2, 12, 30, 56].map(Fr::from));
+        assert_eq!(witnesses[2].table(), &[14, 86].map(Fr::from));
+
+        let mut transcript = PoseidonSponge::default();
+        absorb_circuit_description(&mut transcript, &circuit);
+        let mut claim = output_layer_claim(&mut transcript, &output);
+        let mut layer_proofs = vec![];
+        for layer_idx in (0..circuit.depth()).rev() {
+            assert!(claim.holds_for(witnesses[layer_idx + 1].table()));
+            let (layer_proof, next_claim) =
+                prove_layer(&mut transcript, layer_idx, claim, &circuit).unwrap();
+            layer_proofs.push(layer_proof);
+            claim = next_claim;
+        }
+        assert!(claim.holds_for(circuit.input()));
+
+        // The same messages as proving in one go.
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        for (layer_proof, expected) in layer_proofs.iter().zip(proof.get_layer_proofs()) {
+            assert_eq!(
+                layer_proof.get_sumcheck_proof(),
+                expected.get_sumcheck_proof()
+            );
+            assert_eq!(layer_proof.get_left_eval(), expected.get_left_eval());
+            assert_eq!(layer_proof.get_right_eval(), expected.get_right_eval());
+        }
+    }
+
+    #[test]
+    fn test_check_output_claim() {
+        let circuit = small_circuit();
+        let output = circuit.evaluate();
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        let check = |claimed_output: &[Fr]| {
+            let mut transcript = PoseidonSponge::default();
+            absorb_circuit_description(&mut transcript, &circuit);
+            check_output_claim(&mut transcript, &proof, claimed_output)
+        };
+        assert!(check(&output));
+        assert!(!check(&[output[0], output[1] + Fr::ONE]));
+    }
+
+    #[test]
+    fn test_gkr_public_io() {
+        // hash(x) = x^5 + 3, with x the only public input.
+        let copy = |input| Gate::constant(GateType::ConstMul(Fr::ONE), input);
+        let add_three = |input| Gate::constant(GateType::ConstAdd(Fr::from(3)), input);
+        let layers = vec![
+            GkrLayer::new(vec![Gate::new(GateType::Mul, 0, 0), copy(0)], 2),
+            GkrLayer::new(vec![Gate::new(GateType::Mul, 0, 0), copy(1)], 2),
+            GkrLayer::new(vec![Gate::new(GateType::Mul, 0, 1); 2], 2),
+            GkrLayer::new(vec![add_three(0), add_three(1)], 2),
+        ];
+        let mut circuit = GkrCircuit::new(vec![Fr::ZERO; 2], layers);
+        circuit.set_public_inputs(&[Fr::from(7)]);
+        let output = circuit.get_public_output();
+        assert_eq!(output, vec![Fr::from(7u64.pow(5) + 3); 2]);
+
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+
+        // The output is linked to x: a verifier with a different public input
+        // rejects the same proof and output.
+        let mut other_input = circuit.clone();
+        other_input.set_public_inputs(&[Fr::from(8)]);
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &other_input,
+            &output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_rejects_lookup_gates() {
+        let lookup = GateType::Lookup {
+            table: MultilinearExtension::new(vec![Fr::from(0), Fr::from(1)]),
+            input_vars: 1,
+        };
+        let layer = GkrLayer::new(
+            vec![Gate::new(lookup, 0, 0), Gate::new(GateType::Add, 0, 1)],
+            2,
+        );
+        let circuit = GkrCircuit::new(vec![Fr::from(1), Fr::from(0)], vec![layer]);
+        assert_eq!(
+            gkr_prove(&mut PoseidonSponge::default(), &circuit).err(),
+            Some(GkrError::UnsupportedGate {
+                layer_idx: 0,
+                gate_idx: 0,
+            })
+        );
+
+        // A proof of some other circuit with the same shape is rejected, rather
+        // than panicking on the lookup gate.
+        let other_circuit = GkrCircuit::new(
+            circuit.input().to_vec(),
+            vec![GkrLayer::new(
+                vec![
+                    Gate::new(GateType::Add, 0, 0),
+                    Gate::new(GateType::Add, 0, 1),
+                ],
+                2,
+            )],
+        );
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &other_circuit).unwrap();
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &circuit.evaluate(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_verify_rejects_empty_circuit() {
+        let circuit = GkrCircuit::new(vec![Fr::from(1), Fr::from(2)], vec![]);
+        let proof = GkrProof {
+            layer_proofs: vec![],
+        };
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            circuit.input(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_public_inputs_bound_to_transcript() {
+        let circuit = GkrCircuitBuilder::new()
+            .add_input_layer((1..=4).map(Fr::from).collect())
+            .add_mul_layer(vec![(0, 2), (1, 3)])
+            .add_add_layer(vec![(0, 1)])
+            .build()
+            .unwrap()
+            .with_input_types(vec![
+                InputType::Public,
+                InputType::Public,
+                InputType::Witness,
+                InputType::Witness,
+            ]);
+        assert_eq!(
+            circuit.public_inputs(),
+            vec![(0, Fr::from(1)), (1, Fr::from(2))]
+        );
+        let output = circuit.evaluate();
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+
+        // Same witness, different public input.
+        let mut other_public_input = circuit.clone();
+        other_public_input.set_public_inputs(&[Fr::from(5)]);
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &other_public_input,
+            &output,
+            &proof
+        ));
+
+        // Absorbing the public inputs changes the transcript, and with it the
+        // proof, even for the same input values.
+        let all_witness = GkrCircuit::new(circuit.input().to_vec(), circuit.layers().to_vec());
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &all_witness,
+            &output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_wrong_output() {
+        let circuit = small_circuit();
+        let mut output = circuit.evaluate();
+        output[1] += Fr::ONE;
+
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_linear_combination_gates() {
+        // (3a + 5b - 2c) * (a + d), duplicated across both output gates.
+        let lc_layer = GkrLayer::new(
+            vec![
+                Gate::linear_combination(vec![
+                    (0, Fr::from(3)),
+                    (1, Fr::from(5)),
+                    (2, -Fr::from(2)),
+                ]),
+                Gate::linear_combination(vec![(0, Fr::ONE), (3, Fr::ONE)]),
+            ],
+            4,
+        );
+        let output_layer = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 1),
+                Gate::new(GateType::Mul, 1, 0),
+            ],
+            2,
+        );
+        let circuit = GkrCircuit::new(
+            (1..=4).map(Fr::from).collect(),
+            vec![lc_layer, output_layer],
+        );
+        let output = circuit.evaluate();
+        assert_eq!(output, vec![Fr::from(7 * 5); 2]);
+
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &[Fr::from(7 * 5), Fr::from(36)],
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_constant_gates() {
+        // (3a + 1) * (5b), duplicated across both output gates.
+        let const_layer = GkrLayer::new(
+            vec![
+                Gate::constant(GateType::ConstMul(Fr::from(3)), 0),
+                Gate::constant(GateType::ConstMul(Fr::from(5)), 1),
+            ],
+            2,
+        );
+        let offset_layer = GkrLayer::new(
+            vec![
+                Gate::constant(GateType::ConstAdd(Fr::ONE), 0),
+                Gate::new(GateType::Add, 1, 1),
+            ],
+            2,
+        );
+        let output_layer = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 1),
+                Gate::new(GateType::Mul, 1, 0),
+            ],
+            2,
+        );
+        let circuit = GkrCircuit::new(
+            vec![Fr::from(2), Fr::from(4)],
+            vec![const_layer, offset_layer, output_layer],
+        );
+        let output = circuit.evaluate();
+        assert_eq!(output, vec![Fr::from(7 * 40); 2]);
+
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+
+        let mut wrong_output = output;
+        wrong_output[0] += Fr::ONE;
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &wrong_output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_gkr_different_circuit() {
+        let circuit = small_circuit();
+        let output = circuit.evaluate();
+        let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+
+        // Same shape, but the first add gate is now a mul gate.
+        let mut layers = circuit.layers().to_vec();
+        layers[1] = GkrLayer::new(
+            vec![
+                Gate::new(GateType::Mul, 0, 1),
+                Gate::new(GateType::Add, 2, 3),
+            ],
+            4,
+        );
+        let other_circuit = GkrCircuit::new(circuit.input().to_vec(), layers);
+        assert_eq!(other_circuit.check_well_formed(), Ok(()));
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &other_circuit,
+            &output,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_circuit_description_binds_gates() {
+        let description = |gates: Vec<Gate<Fr>>| {
+            let circuit = GkrCircuit::new(vec![Fr::ONE; 4], vec![GkrLayer::new(gates, 4)]);
+            let mut transcript = PoseidonSponge::default();
+            absorb_circuit_description(&mut transcript, &circuit);
+            transcript.squeeze()
+        };
+        let linear_combination = |coeff| {
+            vec![
+                Gate::linear_combination(vec![(0, Fr::ONE), (2, coeff)]),
+                Gate::constant(GateType::ConstMul(Fr::from(3)), 1),
+            ]
+        };
+        let expected = description(linear_combination(Fr::from(2)));
+        assert_eq!(description(linear_combination(Fr::from(2))), expected);
+
+        // Changing a coefficient, a constant or a wire changes the description.
+        assert_ne!(description(linear_combination(Fr::from(5))), expected);
+        let mut gates = linear_combination(Fr::from(2));
+        gates[1] = Gate::constant(GateType::ConstMul(Fr::from(4)), 1);
+        assert_ne!(description(gates), expected);
+        let mut gates = linear_combination(Fr::from(2));
+        gates[1] = Gate::constant(GateType::ConstMul(Fr::from(3)), 3);
+        assert_ne!(description(gates), expected);
+    }
+
+    #[test]
+    fn test_gkr_tampered_layer_eval() {
+        let circuit = small_circuit();
+        let output = circuit.evaluate();
+
+        let mut proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+        proof.layer_proofs[1].left_eval += Fr::ONE;
+        assert!(!gkr_verify(
+            &mut PoseidonSponge::default(),
+            &circuit,
+            &output,
+            &proof
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod gkr;
 pub mod mle;
+pub mod polynomial_ops;
 pub mod sumcheck;
-pub mod utils;
+pub mod utils;
\ No newline at end of file
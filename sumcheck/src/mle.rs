@@ -1,5 +1,19 @@
-use ark_std::log2;
+use ark_std::{log2, rand::RngCore};
 use shared_types::Field;
+use std::{
+    marker::PhantomData,
+    ops::{Add, Index, IndexMut, Mul, Neg},
+};
+use thiserror::Error;
+
+/// Errors which can occur when constructing a [MultilinearExtension].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MleError {
+    /// The bookkeeping table must have exactly `2^n` entries; holds the
+    /// offending length.
+    #[error("Bookkeeping table length {0} is not a power of two")]
+    NonPowerOfTwoLength(usize),
+}
 
 /// Represents a multilinear polynomial f(x_1, ..., x_n) by storing its explicit
 /// evaluations over the boolean hypercube, i.e. f(b_1, ..., b_n) for
@@ -26,7 +40,7 @@ use shared_types::Field;
 /// multilinear. RHS can be thought of as a multilinear polynomial f'(r_1,x). Now to 
 /// prove f'==f we know that f and f' agree on {0,1}^n, thus f and f' must be equivalent
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MultilinearExtension<F> {
     bookkeping_table: Vec<F>,
     num_vars: usize,
@@ -34,26 +48,167 @@ pub struct MultilinearExtension<F> {
 
 impl<F: Field> MultilinearExtension<F> {
     /// Create a new [MultilinearExtension] from a [`Vec<F>`] of evaluations.
+    ///
+    /// Panics if the number of evaluations is not a power of two; see
+    /// [MultilinearExtension::from_vec_checked] for a non-panicking version.
+    ///
+    /// In debug builds, also runs
+    /// [MultilinearExtension::check_interpolation_consistency] on the new MLE,
+    /// at a point seeded by a randomly keyed hash of its table, so that
+    /// different tables and runs are checked at different points. This costs
+    /// an extra table of `2^n` entries and a few passes over it per MLE.
     pub fn new(bookkeeping_table_vec: Vec<F>) -> Self {
-        let num_vars = log2(bookkeeping_table_vec.len()) as usize;
-        Self {
-            bookkeping_table: bookkeeping_table_vec,
+        let mle = Self::from_vec_checked(bookkeeping_table_vec).unwrap();
+        #[cfg(debug_assertions)]
+        {
+            use ark_std::rand::{rngs::StdRng, SeedableRng};
+            use std::hash::{BuildHasher, RandomState};
+            let seed = RandomState::new().hash_one(&mle.bookkeping_table);
+            assert!(mle.check_interpolation_consistency(&mut StdRng::seed_from_u64(seed)));
+        }
+        mle
+    }
+
+    /// Create a new [MultilinearExtension] from a [`Vec<F>`] of evaluations,
+    /// returning [MleError::NonPowerOfTwoLength] if `table` does not have
+    /// exactly `2^n` entries for some `n`.
+    pub fn from_vec_checked(table: Vec<F>) -> Result<Self, MleError> {
+        if !table.len().is_power_of_two() {
+            return Err(MleError::NonPowerOfTwoLength(table.len()));
+        }
+        let num_vars = log2(table.len()) as usize;
+        Ok(Self {
+            bookkeping_table: table,
             num_vars,
+        })
+    }
+
+    /// Create a new [MultilinearExtension] from a [`Vec<F>`] of evaluations of
+    /// any length, padding `table` with zeros up to the next power of two.
+    pub fn from_vec_padded(mut table: Vec<F>) -> Self {
+        table.resize(table.len().next_power_of_two(), F::ZERO);
+        Self::new(table)
+    }
+
+    /// Flattens a `2^r` by `2^c` table of a bivariate function f(row, col) in
+    /// row-major order, so that the first `r` variables of the resulting MLE
+    /// index the row and the last `c` variables index the column.
+    ///
+    /// Panics if the rows differ in length or if either dimension is not a
+    /// power of two.
+    pub fn from_2d_table(table: &[Vec<F>]) -> Self {
+        assert!(
+            table.len().is_power_of_two(),
+            "Number of rows is not a power of two"
+        );
+        let num_cols = table[0].len();
+        assert!(
+            num_cols.is_power_of_two(),
+            "Number of columns is not a power of two"
+        );
+        assert!(
+            table.iter().all(|row| row.len() == num_cols),
+            "Rows have differing lengths"
+        );
+        Self::new(table.concat())
+    }
+
+    /// The constant polynomial `c` over `num_vars` variables.
+    pub fn constant(c: F, num_vars: usize) -> Self {
+        Self::new(vec![c; 1 << num_vars])
+    }
+
+    /// The zero polynomial over `num_vars` variables.
+    pub fn zero(num_vars: usize) -> Self {
+        Self::constant(F::ZERO, num_vars)
+    }
+
+    /// The constant polynomial one over `num_vars` variables.
+    pub fn one(num_vars: usize) -> Self {
+        Self::constant(F::ONE, num_vars)
+    }
+
+    /// Returns eq(`point`, x) as an MLE in x, i.e. the table holding
+    /// eq(`point`, b) for every b \in \{0, 1}^n, where the first coordinate of
+    /// `point` corresponds to the most significant bit of b's index.
+    pub fn eq_poly(point: &[F]) -> Self {
+        let table = point.iter().fold(vec![F::ONE], |evals, &r| {
+            evals
+                .iter()
+                .flat_map(|&eval| [eval * (F::ONE - r), eval * r])
+                .collect()
+        });
+        // Not `Self::new`, whose consistency check in debug builds calls this.
+        Self {
+            bookkeping_table: table,
+            num_vars: point.len(),
         }
     }
 
+    /// The indicator polynomial \prod_i (b*_i x_i + (1 - b*_i)(1 - x_i)) of the
+    /// boolean point b* = `point`, i.e. [MultilinearExtension::eq_poly] at b*:
+    /// its table is one at the index whose binary encoding is `point` (first
+    /// coordinate most significant) and zero elsewhere.
+    pub fn kronecker_delta(point: &[bool]) -> Self {
+        let point: Vec<F> = point
+            .iter()
+            .map(|&bit| if bit { F::ONE } else { F::ZERO })
+            .collect();
+        Self::eq_poly(&point)
+    }
+
+    /// Interpolates the unique [MultilinearExtension] f with f(`points[i]`) =
+    /// `values[i]`, where the `2^n` points may lie anywhere in F^n. Since
+    /// f(p) = \sum_b eq(p, b) * f(b), this solves the linear system for the
+    /// bookkeeping table whose rows are eq(`points[i]`, .).
+    ///
+    /// Panics if there is not exactly one value per point, if the number of
+    /// points is not a power of two, or if the points do not determine a unique
+    /// multilinear polynomial (e.g. when some of them coincide).
+    pub fn from_evaluations_at_points(points: &[Vec<F>], values: &[F]) -> Self {
+        assert_eq!(points.len(), values.len());
+        assert!(points.len().is_power_of_two());
+        let num_vars = log2(points.len()) as usize;
+        let rows = points
+            .iter()
+            .map(|point| {
+                assert_eq!(point.len(), num_vars);
+                Self::eq_poly(point).bookkeping_table
+            })
+            .collect();
+        let table = solve_linear_system(rows, values.to_vec())
+            .expect("Evaluation points do not determine a unique multilinear extension");
+        Self::new(table)
+    }
+
     /// Returns `n`, the number of arguments `\tilde{f}` takes.
     pub fn num_vars(&self) -> usize {
         self.num_vars
     }
 
+    /// Number of entries stored explicitly in the bookkeeping table.
+    pub fn table_len(&self) -> usize {
+        self.bookkeping_table.len()
+    }
+
+    /// Number of points of the hypercube, `2^n`, including any whose values
+    /// are implicitly zero rather than stored (see [MultilinearExtension::get]).
+    pub fn full_table_len(&self) -> usize {
+        1 << self.num_vars
+    }
+
+    /// Whether the MLE has no variables, i.e. is a constant.
+    pub fn is_empty(&self) -> bool {
+        self.num_vars == 0
+    }
+
     /// Returns the `idx`-th element, if `idx` is in the range `[0,
     /// 2^self.num_vars)`.
     pub fn get(&self, idx: usize) -> Option<F> {
-        if idx >= (1 << self.num_vars()) {
+        if idx >= self.full_table_len() {
             // `idx` is out of range.
             None
-        } else if idx >= self.bookkeping_table.len() {
+        } else if idx >= self.table_len() {
             // `idx` is within range, but value is implicitly assumed to be
             // zero.
             Some(F::ZERO)
@@ -63,22 +218,1460 @@ impl<F: Field> MultilinearExtension<F> {
         }
     }
 
+    /// Returns f(`bits`) for a point of the boolean hypercube, where the first
+    /// bit is the first variable, or `None` if there is not one bit per
+    /// variable.
+    pub fn evaluate_at_boolean(&self, bits: &[bool]) -> Option<F> {
+        if bits.len() != self.num_vars {
+            return None;
+        }
+        self.get(bits_to_idx(bits.iter().copied()))
+    }
+
+    /// Returns f(`bits`) for a point of the boolean hypercube given as field
+    /// elements. Panics if some element is neither zero nor one, or if there is
+    /// not one element per variable.
+    pub fn evaluate_at_binary_field(&self, bits: &[F]) -> F {
+        assert_eq!(bits.len(), self.num_vars);
+        let bits = bits.iter().map(|&bit| {
+            assert!(bit == F::ZERO || bit == F::ONE);
+            bit == F::ONE
+        });
+        self.bookkeping_table[bits_to_idx(bits)]
+    }
+
          /// Return the table
     pub fn table(&self) -> &[F] {
         &self.bookkeping_table
     }
 
+    /// The stored bookkeeping table, as [MultilinearExtension::table].
+    pub fn as_slice(&self) -> &[F] {
+        &self.bookkeping_table
+    }
+
+    /// Mutable access to the stored bookkeeping table, for in-place operations
+    /// on the values which bypass the MLE API.
+    pub fn as_mut_slice(&mut self) -> &mut [F] {
+        &mut self.bookkeping_table
+    }
+
 
     /// Restrict the first variable and update table in place
+    ///
+    /// In debug builds, checks the new table as
+    /// [MultilinearExtension::check_bookkeeping_invariant] does, which keeps a
+    /// copy of the old table and recomputes every entry without folding.
     pub fn restrict_first_var(table: &mut Vec<F>, r: F) {
         if table.len() == 1 { return; }
+        #[cfg(debug_assertions)]
+        let before = table.clone();
+        Self::bookkeeping_step(table, r);
+        #[cfg(debug_assertions)]
+        assert!(
+            is_restriction(&before, &[r], table),
+            "Bookkeeping table invariant violated"
+        );
+    }
+
+    /// One step of the bookkeeping algorithm: replaces the table of f(x_1, ...,
+    /// x_n) with that of f(r, x_2, ..., x_n), halving its length. After `n`
+    /// steps with challenges r_1, ..., r_n, the single remaining entry is
+    /// f(r_1, ..., r_n), i.e. `f.evaluate(&[r_1, ..., r_n])`. Panics unless
+    /// the table's length is a power of two greater than one.
+    pub fn bookkeeping_step(table: &mut Vec<F>, r: F) {
+        assert!(table.len() > 1 && table.len().is_power_of_two());
+        Self::bookkeeping_step_unchecked(table, r)
+    }
+
+    /// [MultilinearExtension::bookkeeping_step] without the length check, for
+    /// tight loops which have already validated the table. The result is
+    /// meaningless unless the table's length is a power of two greater than
+    /// one, but it never reads out of bounds.
+    pub fn bookkeeping_step_unchecked(table: &mut Vec<F>, r: F) {
         let half = table.len() / 2;
-        for i in 0..half {
+        let (low, high) = table.split_at_mut(half);
+        for (l, h) in low.iter_mut().zip(high.iter()) {
             // Use fact f(r_1, b_2, ..., b_n) = (1 - r_1) * f(0, b_2, ..., b_n) + r_1 * f(1, b_2, ..., b_n)
-            let l = table[i];
-            let h = table[i + half];
-            table[i] = (F::ONE - r) * l + r * h;
+            *l += r * (*h - *l);
         }
         table.truncate(half);
     }
+
+    /// Sparse analogue of [MultilinearExtension::restrict_first_var] for the
+    /// nonzero `entries` (index, value) of a table with `2 * half` entries.
+    /// Pairs up (i, f(0, b)) and (i + half, f(1, b)), treating a missing
+    /// partner as zero, so the result never has more entries than the input.
+    pub fn sparse_restrict_first_var(entries: &mut Vec<(usize, F)>, half: usize, r: F) {
+        if half == 0 {
+            return;
+        }
+        let (mut low, mut high): (Vec<_>, Vec<_>) =
+            entries.drain(..).partition(|(idx, _)| *idx < half);
+        low.sort_unstable_by_key(|(idx, _)| *idx);
+        high.iter_mut().for_each(|(idx, _)| *idx -= half);
+        high.sort_unstable_by_key(|(idx, _)| *idx);
+
+        let (mut low, mut high) = (low.into_iter().peekable(), high.into_iter().peekable());
+        loop {
+            let entry = match (low.peek(), high.peek()) {
+                (Some(&(i, l)), Some(&(j, h))) if i == j => {
+                    low.next();
+                    high.next();
+                    (i, (F::ONE - r) * l + r * h)
+                }
+                (Some(&(i, l)), Some(&(j, _))) if i < j => {
+                    low.next();
+                    (i, (F::ONE - r) * l)
+                }
+                (Some(&(i, l)), None) => {
+                    low.next();
+                    (i, (F::ONE - r) * l)
+                }
+                (_, Some(&(j, h))) => {
+                    high.next();
+                    (j, r * h)
+                }
+                (None, None) => break,
+            };
+            entries.push(entry);
+        }
+    }
+
+    /// Returns h = f(g) for this MLE g and the 1-variable MLE f given by
+    /// f(0) = `low_eval` and f(1) = `high_eval`, i.e. f(x) = a + b * x with
+    /// a = `low_eval` and b = `high_eval` - `low_eval`. Since f is affine, h is
+    /// again multilinear, with table entries a + b * g(i).
+    pub fn affine_compose(&self, low_eval: F, high_eval: F) -> MultilinearExtension<F> {
+        let slope = high_eval - low_eval;
+        let table = self
+            .bookkeping_table
+            .iter()
+            .map(|&value| low_eval + slope * value)
+            .collect();
+        Self::new(table)
+    }
+
+    /// The pointwise product \prod_k f_k on the hypercube, as an MLE over the
+    /// largest number of variables among `mles`. As in sumcheck, an MLE with
+    /// fewer variables depends on the first ones, i.e. is padded by repeating
+    /// each entry. Note that off the hypercube this is the MLE of the product
+    /// table, not the (non-multilinear) product of the polynomials, so it does
+    /// not evaluate to the product of the factors' evaluations in general.
+    pub fn product(mles: &[MultilinearExtension<F>]) -> MultilinearExtension<F> {
+        let n = mles.iter().map(|f| f.num_vars).max().unwrap_or(0);
+        let table = (0..1 << n)
+            .map(|idx| {
+                mles.iter().fold(F::ONE, |acc, f| {
+                    acc * f.bookkeping_table[idx >> (n - f.num_vars)]
+                })
+            })
+            .collect();
+        Self::new(table)
+    }
+
+    /// The linear interpolation (1 - r) * f + r * g between two MLEs over the
+    /// same number of variables, which is f at r = 0 and g at r = 1. Restricting
+    /// the first variable to r is the special case f = f(0, ...), g = f(1, ...).
+    pub fn affine_combine(
+        f: &MultilinearExtension<F>,
+        g: &MultilinearExtension<F>,
+        r: F,
+    ) -> MultilinearExtension<F> {
+        f * (F::ONE - r) + g * r
+    }
+
+    /// Applies `f` to every entry of the bookkeeping table. Note that the result
+    /// is the MLE of the mapped table, which in general is not f composed with
+    /// this MLE away from the hypercube (e.g. for `|x| x * x`).
+    pub fn map<G: Field>(&self, f: impl Fn(F) -> G) -> MultilinearExtension<G> {
+        MultilinearExtension::new(
+            self.bookkeping_table
+                .iter()
+                .map(|&value| f(value))
+                .collect(),
+        )
+    }
+
+    /// Like `map`, but also passes the index of each entry to `f`.
+    pub fn map_enumerate<G: Field>(&self, f: impl Fn(usize, F) -> G) -> MultilinearExtension<G> {
+        let table = self
+            .bookkeping_table
+            .iter()
+            .enumerate()
+            .map(|(idx, &value)| f(idx, value))
+            .collect();
+        MultilinearExtension::new(table)
+    }
+
+    /// Returns \sum_{b \in \{0, 1\}^n} f(b), the sum of the whole table.
+    pub fn sum_over_hypercube(&self) -> F {
+        self.bookkeping_table
+            .iter()
+            .fold(F::ZERO, |acc, value| acc + value)
+    }
+
+    /// Returns \sum_{i : mask[i]} f(i), the sum of the table entries selected by
+    /// `mask`. Panics unless `mask` has one entry per point of the hypercube.
+    pub fn sum_over_subset(&self, mask: &[bool]) -> F {
+        assert_eq!(mask.len(), 1 << self.num_vars);
+        self.bookkeping_table
+            .iter()
+            .zip(mask)
+            .filter(|(_, &selected)| selected)
+            .fold(F::ZERO, |acc, (value, _)| acc + value)
+    }
+
+    /// Returns g(x_1, ..., x_{n - k}) = \sum_{b \in \{0, 1\}^k} f(x_1, ..., x_{n - k}, b).
+    /// Since the last variables are the least significant bits of the index,
+    /// each entry of g sums a run of `2^k` consecutive entries of f. Panics if
+    /// `k` exceeds the number of variables.
+    pub fn partial_sum_over_last_k_vars(&self, k: usize) -> MultilinearExtension<F> {
+        assert!(k <= self.num_vars);
+        let table = self
+            .bookkeping_table
+            .chunks(1 << k)
+            .map(|chunk| chunk.iter().fold(F::ZERO, |acc, x| acc + x))
+            .collect();
+        Self::new(table)
+    }
+
+    /// Fixes the variable x_i to r for every (i, r) in `fixed`, given in any
+    /// order, and returns the MLE over the remaining variables in their original
+    /// order. Variables are restricted from the last to the first, so that
+    /// fixing one never shifts the index of another still to be fixed. Panics
+    /// if a variable is out of range or fixed twice.
+    pub fn restrict_at_fixed_coords(&self, fixed: &[(usize, F)]) -> MultilinearExtension<F> {
+        let mut fixed = fixed.to_vec();
+        fixed.sort_by(|(a, _), (b, _)| b.cmp(a));
+        assert!(fixed.windows(2).all(|pair| pair[0].0 != pair[1].0));
+        assert!(fixed.first().is_none_or(|(var, _)| *var < self.num_vars));
+
+        let mut table = self.bookkeping_table.clone();
+        let mut num_vars = self.num_vars;
+        for (var, r) in fixed {
+            let bit = 1 << (num_vars - 1 - var);
+            table = (0..table.len() / 2)
+                .map(|idx| {
+                    // Insert a zero bit for x_i into the remaining variables' index.
+                    let low = ((idx & !(bit - 1)) << 1) | (idx & (bit - 1));
+                    table[low] + r * (table[low | bit] - table[low])
+                })
+                .collect();
+            num_vars -= 1;
+        }
+        Self::new(table)
+    }
+
+    /// Returns g(x_1, ..., x_n) = f(x_{sigma(1)}, ..., x_{sigma(n)}), where
+    /// `sigma` is a permutation of [0, n). Panics if it is not.
+    pub fn permute_vars(&self, sigma: &[usize]) -> MultilinearExtension<F> {
+        let mut seen = vec![false; self.num_vars];
+        assert_eq!(sigma.len(), self.num_vars);
+        for &var in sigma {
+            assert!(!std::mem::replace(&mut seen[var], true));
+        }
+        let table = (0..self.bookkeping_table.len())
+            .map(|idx| self.bookkeping_table[permute_index(idx, sigma)])
+            .collect();
+        Self::new(table)
+    }
+
+    /// Möbius transform over the variables selected by `mask`, where bit `i`
+    /// of `mask` selects the `i`-th variable: for each selected variable x_i,
+    /// every entry with b_i = 1 has the entry with b_i = 0 subtracted from it.
+    /// Over all variables this yields the coefficient representation, i.e. the
+    /// entry at b is the coefficient of \prod_{i : b_i = 1} x_i. Note that the
+    /// transform is not an involution; its inverse adds rather than subtracts.
+    pub fn inclusion_exclusion_transform(&self, mask: usize) -> MultilinearExtension<F> {
+        let n = self.num_vars;
+        let mut table = self.bookkeping_table.clone();
+        for var in (0..n).filter(|var| (mask >> var) & 1 == 1) {
+            let bit = 1 << (n - 1 - var);
+            for idx in (0..table.len()).filter(|idx| idx & bit != 0) {
+                let low = table[idx ^ bit];
+                table[idx] -= low;
+            }
+        }
+        Self::new(table)
+    }
+
+    /// Evaluates the MLE at the `2^n` consecutive powers 1, w, ..., w^{2^n - 1}
+    /// of `generator` w, where a power y stands for the point
+    /// (y^{2^{n - 1}}, ..., y^2, y). Under this substitution the MLE becomes
+    /// the univariate polynomial whose coefficients are those of the MLE's
+    /// coefficient form (see [MultilinearExtension::inclusion_exclusion_transform]),
+    /// which is evaluated at each power by Horner's rule.
+    pub fn evaluate_on_coset(&self, generator: F) -> Vec<F> {
+        let coefficients = self.inclusion_exclusion_transform((1 << self.num_vars) - 1);
+        let mut power = F::ONE;
+        (0..coefficients.bookkeping_table.len())
+            .map(|_| {
+                let eval = coefficients
+                    .bookkeping_table
+                    .iter()
+                    .rev()
+                    .fold(F::ZERO, |acc, coeff| acc * power + coeff);
+                power *= generator;
+                eval
+            })
+            .collect()
+    }
+
+    /// Evaluates the multilinear extension whose bookkeeping table is `table`
+    /// at `challenges` by restricting its variables one at a time, starting
+    /// with the first. Panics if `table` does not have `2^challenges.len()`
+    /// entries.
+    pub fn evaluate_via_folding(mut table: Vec<F>, challenges: &[F]) -> F {
+        assert_eq!(table.len(), 1 << challenges.len());
+        for &r in challenges {
+            Self::restrict_first_var(&mut table, r);
+        }
+        table[0]
+    }
+
+    /// The sequence of MLEs f, f(r_1, .), f(r_1, r_2, .), ..., f(r_1, ..., r_k)
+    /// which a sumcheck prover folds the bookkeeping table `initial` through
+    /// for `challenges` = (r_1, ..., r_k), for inspecting each intermediate
+    /// stage. Panics if `initial` does not have a power-of-two length of at
+    /// least `2^challenges.len()`.
+    pub fn from_folding_sequence(initial: Vec<F>, challenges: &[F]) -> Vec<Self> {
+        assert!(initial.len().is_power_of_two());
+        assert!(initial.len() >= 1 << challenges.len());
+        let mut table = initial;
+        let mut sequence = vec![Self::new(table.clone())];
+        for &r in challenges {
+            Self::restrict_first_var(&mut table, r);
+            sequence.push(Self::new(table.clone()));
+        }
+        sequence
+    }
+
+    /// Restricts every variable of this MLE to `challenges`, consuming it and
+    /// returning the resulting constant f(r_1, ..., r_n).
+    pub fn restrict_all_vars(self, challenges: &[F]) -> F {
+        Self::evaluate_via_folding(self.bookkeping_table, challenges)
+    }
+
+    /// Returns f(r_1, ..., r_n) for `point` = (r_1, ..., r_n).
+    pub fn evaluate(&self, point: &[F]) -> F {
+        Self::evaluate_via_folding(self.bookkeping_table.clone(), point)
+    }
+
+    /// Compares this MLE with `other` at every point of the hypercube, for
+    /// debugging. Returns the first index at which they differ along with both
+    /// values there. Panics if the two have different numbers of variables.
+    #[cfg(debug_assertions)]
+    pub fn assert_equals_on_hypercube(
+        &self,
+        other: &MultilinearExtension<F>,
+    ) -> Result<(), (usize, F, F)> {
+        assert_eq!(self.num_vars, other.num_vars);
+        (0..1 << self.num_vars).try_for_each(|idx| {
+            let (self_val, other_val) = (self.get(idx).unwrap(), other.get(idx).unwrap());
+            if self_val == other_val {
+                Ok(())
+            } else {
+                Err((idx, self_val, other_val))
+            }
+        })
+    }
+
+    /// Evaluates this MLE at a random point r two ways, by folding and as
+    /// \sum_b eq(r, b) * f(b), and checks that they agree. This catches bugs in
+    /// either [MultilinearExtension::evaluate] or
+    /// [MultilinearExtension::eq_poly].
+    #[cfg(debug_assertions)]
+    pub fn check_interpolation_consistency(&self, rng: &mut impl RngCore) -> bool {
+        let point: Vec<F> = (0..self.num_vars).map(|_| F::random(&mut *rng)).collect();
+        let via_eq = Self::eq_poly(&point)
+            .bookkeping_table
+            .iter()
+            .zip(&self.bookkeping_table)
+            .fold(F::ZERO, |acc, (&eq, &value)| acc + eq * value);
+        self.evaluate(&point) == via_eq
+    }
+
+    /// Sanity check of a bookkeeping table, for debugging, e.g. after a
+    /// sequence of restrictions: whether `restricted` is the table of
+    /// f(`challenges`, x) for this MLE f. That is, for `k` challenges it must
+    /// hold `2^(n - k)` entries, the `b`-th of which is
+    /// \sum_a eq(`challenges`, a) * f(a, b). The entries are recomputed via
+    /// [MultilinearExtension::eq_poly] rather than by folding, in O(2^n) field
+    /// operations.
+    pub fn check_bookkeeping_invariant(&self, challenges: &[F], restricted: &[F]) -> bool {
+        is_restriction(&self.bookkeping_table, challenges, restricted)
+    }
+
+    /// Evaluates `mle_a` and `mle_b` at the same uniformly random point and
+    /// returns whether they agree. By Schwartz-Zippel, two distinct n-variate
+    /// MLEs agree with probability at most n / |F|. MLEs over different numbers
+    /// of variables never agree.
+    pub fn sample_at_random_point(
+        mle_a: &MultilinearExtension<F>,
+        mle_b: &MultilinearExtension<F>,
+        rng: &mut impl RngCore,
+    ) -> bool {
+        if mle_a.num_vars != mle_b.num_vars {
+            return false;
+        }
+        let point: Vec<F> = (0..mle_a.num_vars).map(|_| F::random(&mut *rng)).collect();
+        mle_a.evaluate(&point) == mle_b.evaluate(&point)
+    }
+
+    /// Repeats [MultilinearExtension::sample_at_random_point] `num_checks`
+    /// times, for a soundness error of (n / |F|)^`num_checks`.
+    pub fn mle_equals(
+        a: &MultilinearExtension<F>,
+        b: &MultilinearExtension<F>,
+        rng: &mut impl RngCore,
+        num_checks: usize,
+    ) -> bool {
+        (0..num_checks).all(|_| Self::sample_at_random_point(a, b, rng))
+    }
+
+    /// Returns \sum_g eq(`alpha`, g) * f(g), i.e. f(`alpha`).
+    #[deprecated(note = "Renamed to `eq_weighted_sum`")]
+    pub fn weighted_sum_with_eq(&self, alpha: &[F]) -> F {
+        self.eq_weighted_sum(alpha)
+    }
+
+    /// Returns \sum_b eq(`alpha`, b) * f(b), the dot product of the bookkeeping
+    /// table with the table of eq(`alpha`, .), without building the latter. This
+    /// equals f(`alpha`). The first fold reads straight from the bookkeeping
+    /// table into a buffer of half its size, and each later fold halves that
+    /// buffer, for O(2^n) field operations in total.
+    pub fn eq_weighted_sum(&self, alpha: &[F]) -> F {
+        assert_eq!(alpha.len(), self.num_vars);
+        let Some((&r, rest)) = alpha.split_first() else {
+            return self.bookkeping_table[0];
+        };
+        let (low, high) = self
+            .bookkeping_table
+            .split_at(self.bookkeping_table.len() / 2);
+        let mut table = low
+            .iter()
+            .zip(high)
+            .map(|(&l, &h)| l + r * (h - l))
+            .collect();
+        for &r in rest {
+            Self::restrict_first_var(&mut table, r);
+        }
+        table[0]
+    }
+
+    /// Monte Carlo check that `evaluate` behaves multilinearly: at each of
+    /// `num_checks` random points r, and for each pair of variables (i, j),
+    /// interpolates f(r) bilinearly from the four points obtained by setting
+    /// r_i, r_j to the boolean corners. Also checks that evaluating at a random
+    /// hypercube point agrees with the bookkeeping table. Note that every table
+    /// defines some multilinear polynomial, so this catches bugs in evaluation
+    /// rather than wrong table entries.
+    pub fn sample_multilinearity(&self, rng: &mut impl RngCore, num_checks: usize) -> bool {
+        let n = self.num_vars;
+        (0..num_checks).all(|_| {
+            let idx = rng.next_u64() as usize % self.bookkeping_table.len();
+            let bits: Vec<F> = (0..n)
+                .map(|i| F::from(((idx >> (n - 1 - i)) & 1) as u64))
+                .collect();
+            if self.evaluate(&bits) != self.bookkeping_table[idx] {
+                return false;
+            }
+
+            let point: Vec<F> = (0..n).map(|_| F::random(&mut *rng)).collect();
+            let eval = self.evaluate(&point);
+            let eq = |bit: bool, r: F| if bit { r } else { F::ONE - r };
+            (0..n).all(|i| {
+                (i + 1..n).all(|j| {
+                    let interpolated = (0..4).fold(F::ZERO, |acc, corner| {
+                        let (bit_i, bit_j) = (corner & 2 != 0, corner & 1 != 0);
+                        let mut corner_point = point.clone();
+                        corner_point[i] = F::from(bit_i as u64);
+                        corner_point[j] = F::from(bit_j as u64);
+                        let weight = eq(bit_i, point[i]) * eq(bit_j, point[j]);
+                        acc + weight * self.evaluate(&corner_point)
+                    });
+                    interpolated == eval
+                })
+            })
+        })
+    }
+}
+
+/// The zero polynomial in one variable, i.e. the table [0, 0].
+impl<F: Field> Default for MultilinearExtension<F> {
+    fn default() -> Self {
+        Self::new(vec![F::ZERO; 2])
+    }
+}
+
+/// The MLE of -f, negating every entry of the bookkeeping table.
+impl<F: Field> Neg for &MultilinearExtension<F> {
+    type Output = MultilinearExtension<F>;
+
+    fn neg(self) -> MultilinearExtension<F> {
+        self.map(|value| -value)
+    }
+}
+
+impl<F: Field> Neg for MultilinearExtension<F> {
+    type Output = MultilinearExtension<F>;
+
+    fn neg(self) -> MultilinearExtension<F> {
+        -&self
+    }
+}
+
+/// The MLE of c * f, scaling every entry of the bookkeeping table.
+impl<F: Field> Mul<F> for &MultilinearExtension<F> {
+    type Output = MultilinearExtension<F>;
+
+    fn mul(self, scalar: F) -> MultilinearExtension<F> {
+        self.map(|value| value * scalar)
+    }
+}
+
+impl<F: Field> Mul<F> for MultilinearExtension<F> {
+    type Output = MultilinearExtension<F>;
+
+    fn mul(self, scalar: F) -> MultilinearExtension<F> {
+        &self * scalar
+    }
+}
+
+/// The MLE of f + g, adding the bookkeeping tables entrywise. Panics if f and
+/// g have different numbers of variables.
+impl<F: Field> Add for MultilinearExtension<F> {
+    type Output = MultilinearExtension<F>;
+
+    fn add(mut self, other: MultilinearExtension<F>) -> MultilinearExtension<F> {
+        assert_eq!(self.num_vars, other.num_vars);
+        for (value, other_value) in self.bookkeping_table.iter_mut().zip(other.bookkeping_table) {
+            *value += other_value;
+        }
+        self
+    }
+}
+
+/// `mle[idx]` is the `idx`-th entry of the bookkeeping table. Since the table
+/// always holds all `2^n` entries, this panics exactly when
+/// [MultilinearExtension::get] returns `None`.
+impl<F: Field> Index<usize> for MultilinearExtension<F> {
+    type Output = F;
+
+    fn index(&self, idx: usize) -> &F {
+        &self.bookkeping_table[idx]
+    }
+}
+
+impl<F: Field> IndexMut<usize> for MultilinearExtension<F> {
+    fn index_mut(&mut self, idx: usize) -> &mut F {
+        &mut self.bookkeping_table[idx]
+    }
+}
+
+/// A [MultilinearExtension] stored by the (index, value) pairs of its nonzero
+/// bookkeeping table entries; every other entry is zero.
+#[derive(Clone, Debug)]
+pub struct SparseMle<F> {
+    entries: Vec<(usize, F)>,
+    num_vars: usize,
+}
+
+impl<F: Field> SparseMle<F> {
+    /// Create a new [SparseMle] over `num_vars` variables. Panics if an index
+    /// is out of range.
+    pub fn new(entries: Vec<(usize, F)>, num_vars: usize) -> Self {
+        assert!(entries.iter().all(|(idx, _)| *idx < (1 << num_vars)));
+        Self { entries, num_vars }
+    }
+
+    /// Keeps only the nonzero entries of `mle`'s bookkeeping table.
+    pub fn from_dense(mle: &MultilinearExtension<F>) -> Self {
+        let entries = mle
+            .table()
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != F::ZERO)
+            .map(|(idx, value)| (idx, *value))
+            .collect();
+        Self::new(entries, mle.num_vars())
+    }
+
+    pub fn to_dense(&self) -> MultilinearExtension<F> {
+        let mut table = vec![F::ZERO; 1 << self.num_vars];
+        for (idx, value) in &self.entries {
+            table[*idx] += *value;
+        }
+        MultilinearExtension::new(table)
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn entries(&self) -> &[(usize, F)] {
+        &self.entries
+    }
+
+    /// Restrict the first variable to `r`, see
+    /// [MultilinearExtension::sparse_restrict_first_var].
+    pub fn restrict_first_var(&mut self, r: F) {
+        if self.num_vars == 0 {
+            return;
+        }
+        let half = 1 << (self.num_vars - 1);
+        MultilinearExtension::sparse_restrict_first_var(&mut self.entries, half, r);
+        self.num_vars -= 1;
+    }
+}
+
+/// Read access to the bookkeeping table of a multilinear extension, whether it
+/// is stored explicitly or computed on demand.
+pub trait MleTable<F> {
+    /// Returns `n`, the number of arguments `\tilde{f}` takes.
+    fn num_vars(&self) -> usize;
+
+    /// Returns the `idx`-th element, if `idx` is in the range `[0,
+    /// 2^self.num_vars)`.
+    fn get(&self, idx: usize) -> Option<F>;
+
+    /// Returns the full bookkeeping table.
+    fn table_as_dense(&self) -> Vec<F>;
+}
+
+impl<F: Field> MleTable<F> for MultilinearExtension<F> {
+    fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    fn get(&self, idx: usize) -> Option<F> {
+        MultilinearExtension::get(self, idx)
+    }
+
+    fn table_as_dense(&self) -> Vec<F> {
+        self.bookkeping_table.clone()
+    }
+}
+
+/// A multilinear extension whose bookkeeping table entries are computed by
+/// `eval_fn` whenever they are needed, for structured polynomials whose
+/// entries are cheap to compute. The table is only materialised once the MLE
+/// is restricted.
+pub struct LazyMle<F, Ev: Fn(usize) -> F> {
+    num_vars: usize,
+    eval_fn: Ev,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, Ev: Fn(usize) -> F> LazyMle<F, Ev> {
+    pub fn new(num_vars: usize, eval_fn: Ev) -> Self {
+        Self {
+            num_vars,
+            eval_fn,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Materialises the table and restricts its first variable to `r`.
+    pub fn restrict_first_var(&self, r: F) -> MultilinearExtension<F> {
+        let mut table = self.table_as_dense();
+        MultilinearExtension::restrict_first_var(&mut table, r);
+        MultilinearExtension::new(table)
+    }
+}
+
+impl<F: Field, Ev: Fn(usize) -> F> MleTable<F> for LazyMle<F, Ev> {
+    fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    fn get(&self, idx: usize) -> Option<F> {
+        (idx < (1 << self.num_vars)).then(|| (self.eval_fn)(idx))
+    }
+
+    fn table_as_dense(&self) -> Vec<F> {
+        (0..(1 << self.num_vars)).map(&self.eval_fn).collect()
+    }
+}
+
+/// A function on the hypercube given by an arithmetic expression over its
+/// variables, evaluated at each point on demand instead of being stored as a
+/// table of `2^n` entries. `Var(i)` is the `i`-th variable, i.e. the `i`-th
+/// most significant bit of the index.
+///
+/// As long as no variable is multiplied with itself, the expression is a
+/// multilinear polynomial and therefore agrees with the MLE of its table
+/// everywhere, not just on the hypercube.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArithmeticMle<F: Field> {
+    Constant(F),
+    Var(usize),
+    Add(Box<ArithmeticMle<F>>, Box<ArithmeticMle<F>>),
+    Mul(Box<ArithmeticMle<F>>, Box<ArithmeticMle<F>>),
+}
+
+impl<F: Field> ArithmeticMle<F> {
+    /// The value of the expression at table index `idx` of a `num_vars`
+    /// variable hypercube.
+    pub fn evaluate_at_idx(&self, idx: usize, num_vars: usize) -> F {
+        match self {
+            ArithmeticMle::Constant(constant) => *constant,
+            ArithmeticMle::Var(var) => F::from(((idx >> (num_vars - 1 - var)) & 1) as u64),
+            ArithmeticMle::Add(left, right) => {
+                left.evaluate_at_idx(idx, num_vars) + right.evaluate_at_idx(idx, num_vars)
+            }
+            ArithmeticMle::Mul(left, right) => {
+                left.evaluate_at_idx(idx, num_vars) * right.evaluate_at_idx(idx, num_vars)
+            }
+        }
+    }
+
+    /// Substitutes `r` for the first variable and shifts every other variable
+    /// down by one, folding subexpressions which become constant.
+    pub fn restrict_first_var(&self, r: F) -> ArithmeticMle<F> {
+        match self {
+            ArithmeticMle::Constant(constant) => ArithmeticMle::Constant(*constant),
+            ArithmeticMle::Var(0) => ArithmeticMle::Constant(r),
+            ArithmeticMle::Var(var) => ArithmeticMle::Var(var - 1),
+            ArithmeticMle::Add(left, right) => {
+                match (left.restrict_first_var(r), right.restrict_first_var(r)) {
+                    (ArithmeticMle::Constant(l), ArithmeticMle::Constant(r)) => {
+                        ArithmeticMle::Constant(l + r)
+                    }
+                    (left, right) => ArithmeticMle::Add(Box::new(left), Box::new(right)),
+                }
+            }
+            ArithmeticMle::Mul(left, right) => {
+                match (left.restrict_first_var(r), right.restrict_first_var(r)) {
+                    (ArithmeticMle::Constant(l), ArithmeticMle::Constant(r)) => {
+                        ArithmeticMle::Constant(l * r)
+                    }
+                    (left, right) => ArithmeticMle::Mul(Box::new(left), Box::new(right)),
+                }
+            }
+        }
+    }
+}
+
+/// The table index of a point of the boolean hypercube, whose first bit is the
+/// most significant.
+fn bits_to_idx(bits: impl Iterator<Item = bool>) -> usize {
+    bits.fold(0, |idx, bit| (idx << 1) | bit as usize)
+}
+
+/// Reinterprets the bits of `idx` so that the bit of variable `j` in the
+/// result is the bit of variable `sigma[j]` in `idx`, where variable 0 is the
+/// most significant bit.
+fn permute_index(idx: usize, sigma: &[usize]) -> usize {
+    let n = sigma.len();
+    sigma.iter().enumerate().fold(0, |acc, (j, &var)| {
+        acc | (((idx >> (n - 1 - var)) & 1) << (n - 1 - j))
+    })
+}
+
+/// Solves `matrix` * x = `rhs` for a square, invertible `matrix` by Gaussian
+/// elimination, returning `None` if `matrix` is singular.
+fn solve_linear_system<F: Field>(mut matrix: Vec<Vec<F>>, mut rhs: Vec<F>) -> Option<Vec<F>> {
+    let n = rhs.len();
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| matrix[row][col] != F::ZERO)?;
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+
+        let inv = matrix[col][col].invert().unwrap();
+        matrix[col].iter_mut().for_each(|entry| *entry *= inv);
+        rhs[col] *= inv;
+
+        let (pivot_row, pivot_rhs) = (matrix[col].clone(), rhs[col]);
+        for row in (0..n).filter(|&row| row != col) {
+            let factor = matrix[row][col];
+            if factor == F::ZERO {
+                continue;
+            }
+            for (entry, pivot_entry) in matrix[row].iter_mut().zip(&pivot_row).skip(col) {
+                *entry -= factor * pivot_entry;
+            }
+            rhs[row] -= factor * pivot_rhs;
+        }
+    }
+    Some(rhs)
+}
+
+/// [MultilinearExtension::check_bookkeeping_invariant] for the table of f,
+/// without building an MLE from it.
+fn is_restriction<F: Field>(table: &[F], challenges: &[F], restricted: &[F]) -> bool {
+    if challenges.len() > log2(table.len()) as usize
+        || restricted.len().checked_mul(1 << challenges.len()) != Some(table.len())
+    {
+        return false;
+    }
+    let eq_table = MultilinearExtension::eq_poly(challenges).bookkeping_table;
+    restricted.iter().enumerate().all(|(b, &value)| {
+        let expected = eq_table.iter().enumerate().fold(F::ZERO, |acc, (a, &eq)| {
+            acc + eq * table[a * restricted.len() + b]
+        });
+        value == expected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mle::{ArithmeticMle, LazyMle, MleError, MleTable, MultilinearExtension, SparseMle};
+    use ark_std::{rand::Rng, test_rng};
+    use shared_types::{ff_field, Fr};
+
+    /// Evaluates f(r) = \sum_b eq(r, b) * f(b) term by term, where the first
+    /// coordinate of `point` corresponds to the most significant bit of b.
+    fn evaluate_over_hypercube(mle: &MultilinearExtension<Fr>, point: &[Fr]) -> Fr {
+        let n = mle.num_vars();
+        (0..(1 << n)).fold(Fr::ZERO, |acc, b| {
+            let eq = point.iter().enumerate().fold(Fr::ONE, |eq, (i, &r)| {
+                if (b >> (n - 1 - i)) & 1 == 1 {
+                    eq * r
+                } else {
+                    eq * (Fr::ONE - r)
+                }
+            });
+            acc + eq * mle.get(b).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_from_vec_checked_rejects_non_power_of_two() {
+        let table = (0..6).map(|x| Fr::from(x as u64)).collect();
+        let result = MultilinearExtension::<Fr>::from_vec_checked(table);
+        assert_eq!(result.err(), Some(MleError::NonPowerOfTwoLength(6)));
+
+        let result = MultilinearExtension::<Fr>::from_vec_checked(vec![]);
+        assert_eq!(result.err(), Some(MleError::NonPowerOfTwoLength(0)));
+    }
+
+    #[test]
+    fn test_from_vec_padded() {
+        let table: Vec<Fr> = (1..=6).map(|x| Fr::from(x as u64)).collect();
+        let mle = MultilinearExtension::from_vec_padded(table.clone());
+        assert_eq!(mle.num_vars(), 3);
+        assert_eq!(mle.table().len(), 8);
+        assert_eq!(&mle.table()[..6], &table[..]);
+        assert_eq!(&mle.table()[6..], &[Fr::ZERO, Fr::ZERO]);
+    }
+
+    #[test]
+    fn test_evaluate_matches_sum_over_hypercube() {
+        let mut rng = test_rng();
+        for num_vars in 0..5 {
+            let table = (0..(1 << num_vars))
+                .map(|_| Fr::from(rng.gen::<u64>()))
+                .collect();
+            let mle = MultilinearExtension::new(table);
+            let point: Vec<Fr> = (0..num_vars).map(|_| Fr::from(rng.gen::<u64>())).collect();
+
+            let expected = evaluate_over_hypercube(&mle, &point);
+            assert_eq!(mle.evaluate(&point), expected);
+            assert_eq!(
+                MultilinearExtension::evaluate_via_folding(mle.table().to_vec(), &point),
+                expected
+            );
+            assert_eq!(mle.restrict_all_vars(&point), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_evaluations_at_points() {
+        let mut rng = test_rng();
+        let num_vars = 3;
+        let points: Vec<Vec<Fr>> = (0..(1 << num_vars))
+            .map(|_| (0..num_vars).map(|_| Fr::from(rng.gen::<u64>())).collect())
+            .collect();
+        let values: Vec<Fr> = (0..(1 << num_vars))
+            .map(|_| Fr::from(rng.gen::<u64>()))
+            .collect();
+
+        let mle = MultilinearExtension::from_evaluations_at_points(&points, &values);
+        for (point, value) in points.iter().zip(&values) {
+            assert_eq!(mle.evaluate(point), *value);
+        }
+
+        // On the hypercube itself, interpolation recovers the table.
+        let hypercube: Vec<Vec<Fr>> = (0..(1 << num_vars))
+            .map(|b: usize| {
+                (0..num_vars)
+                    .map(|i| Fr::from(((b >> (num_vars - 1 - i)) & 1) as u64))
+                    .collect()
+            })
+            .collect();
+        let mle = MultilinearExtension::from_evaluations_at_points(&hypercube, &values);
+        assert_eq!(mle.table(), &values[..]);
+    }
+
+    #[test]
+    fn test_sparse_restrict_first_var() {
+        let mut rng = test_rng();
+        let num_vars = 10;
+        let table: Vec<Fr> = (0..(1 << num_vars))
+            .map(|_| {
+                if rng.gen_bool(0.05) {
+                    Fr::from(rng.gen::<u64>())
+                } else {
+                    Fr::ZERO
+                }
+            })
+            .collect();
+        let mut dense = table.clone();
+        let mut sparse = SparseMle::from_dense(&MultilinearExtension::new(table));
+
+        for _ in 0..num_vars {
+            let r = Fr::from(rng.gen::<u64>());
+            let num_entries = sparse.entries().len();
+            MultilinearExtension::restrict_first_var(&mut dense, r);
+            sparse.restrict_first_var(r);
+            assert!(sparse.entries().len() <= num_entries);
+            assert_eq!(sparse.to_dense().table(), &dense[..]);
+        }
+    }
+
+    #[test]
+    fn test_lazy_identity_mle() {
+        let num_vars = 4;
+        let identity = LazyMle::new(num_vars, |k| Fr::from(k as u64));
+        for k in 0..(1 << num_vars) {
+            assert_eq!(identity.get(k), Some(Fr::from(k as u64)));
+        }
+        assert_eq!(identity.get(1 << num_vars), None);
+
+        let dense = MultilinearExtension::new(identity.table_as_dense());
+        let r = Fr::from(7);
+        let mut table = dense.table().to_vec();
+        MultilinearExtension::restrict_first_var(&mut table, r);
+        assert_eq!(identity.restrict_first_var(r).table(), &table[..]);
+    }
+
+    #[test]
+    fn test_arithmetic_identity_mle() {
+        // \sum_i 2^{n - 1 - i} * x_i, i.e. the index itself.
+        let num_vars = 3;
+        let identity = (0..num_vars).fold(ArithmeticMle::Constant(Fr::ZERO), |acc, i| {
+            let term = ArithmeticMle::Mul(
+                Box::new(ArithmeticMle::Constant(Fr::from(1 << (num_vars - 1 - i)))),
+                Box::new(ArithmeticMle::Var(i)),
+            );
+            ArithmeticMle::Add(Box::new(acc), Box::new(term))
+        });
+        assert_eq!(identity.evaluate_at_idx(5, num_vars), Fr::from(5));
+        for idx in 0..8 {
+            assert_eq!(
+                identity.evaluate_at_idx(idx, num_vars),
+                Fr::from(idx as u64)
+            );
+        }
+
+        let r = Fr::from(7);
+        let mut table: Vec<Fr> = (0..8).map(Fr::from).collect();
+        MultilinearExtension::restrict_first_var(&mut table, r);
+        let restricted = identity.restrict_first_var(r);
+        for (idx, value) in table.iter().enumerate() {
+            assert_eq!(restricted.evaluate_at_idx(idx, num_vars - 1), *value);
+        }
+        assert_eq!(
+            restricted
+                .restrict_first_var(r)
+                .restrict_first_var(r)
+                .evaluate_at_idx(0, 0),
+            Fr::from(7 * 4 + 7 * 2 + 7)
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        let mle = MultilinearExtension::<Fr>::default();
+        assert_eq!(mle.num_vars(), 1);
+        assert_eq!(mle.sum_over_subset(&[true; 2]), Fr::ZERO);
+        assert_eq!(mle.evaluate(&[Fr::from(7)]), Fr::ZERO);
+    }
+
+    #[test]
+    fn test_affine_combine() {
+        let mut rng = test_rng();
+        let f = MultilinearExtension::new((0..8).map(|_| Fr::random(&mut rng)).collect());
+        let g = MultilinearExtension::new((0..8).map(|_| Fr::random(&mut rng)).collect());
+        assert_eq!(MultilinearExtension::affine_combine(&f, &g, Fr::ZERO), f);
+        assert_eq!(MultilinearExtension::affine_combine(&f, &g, Fr::ONE), g);
+
+        let r = Fr::random(&mut rng);
+        let point: Vec<Fr> = (0..3).map(|_| Fr::random(&mut rng)).collect();
+        let combined = MultilinearExtension::affine_combine(&f, &g, r);
+        assert_eq!(
+            combined.evaluate(&point),
+            (Fr::ONE - r) * f.evaluate(&point) + r * g.evaluate(&point)
+        );
+        assert_eq!(
+            (f.clone() * r + g.clone()).evaluate(&point),
+            r * f.evaluate(&point) + g.evaluate(&point)
+        );
+    }
+
+    #[test]
+    fn test_neg() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table);
+        let negated = -&mle;
+
+        let sum = mle.map_enumerate(|idx, value| value + negated[idx]);
+        assert!(sum.table().iter().all(|value| *value == Fr::ZERO));
+        assert_eq!(sum.sum_over_subset(&[true; 8]), Fr::ZERO);
+
+        let point: Vec<Fr> = (0..3).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        assert_eq!(negated.evaluate(&point), -mle.evaluate(&point));
+        assert_eq!(-negated, mle);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut rng = test_rng();
+        let table = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mut mle = MultilinearExtension::new(table);
+        for idx in 0..8 {
+            assert_eq!(mle[idx], mle.get(idx).unwrap());
+        }
+
+        mle[5] = Fr::from(42);
+        assert_eq!(mle.get(5), Some(Fr::from(42)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_range() {
+        let mle = MultilinearExtension::new(vec![Fr::ONE; 4]);
+        let _ = mle[4];
+    }
+
+    #[test]
+    fn test_permute_vars() {
+        let mut rng = test_rng();
+        let table = (0..16).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table);
+
+        let identity = mle.permute_vars(&[0, 1, 2, 3]);
+        assert_eq!(identity.table(), mle.table());
+
+        let sigma = [2, 0, 3, 1];
+        let sigma_inv = [1, 3, 0, 2];
+        let permuted = mle.permute_vars(&sigma);
+        assert_eq!(permuted.permute_vars(&sigma_inv).table(), mle.table());
+
+        let point: Vec<Fr> = (0..4).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let permuted_point: Vec<Fr> = sigma.iter().map(|&var| point[var]).collect();
+        assert_eq!(permuted.evaluate(&point), mle.evaluate(&permuted_point));
+    }
+
+    #[test]
+    fn test_evaluate_at_boolean() {
+        let table: Vec<Fr> = (0..8).map(|x| Fr::from(x as u64)).collect();
+        let mle = MultilinearExtension::new(table);
+        for idx in 0..8 {
+            let bits: Vec<bool> = (0..3).map(|i| (idx >> (2 - i)) & 1 == 1).collect();
+            let field_bits: Vec<Fr> = bits.iter().map(|&bit| Fr::from(bit as u64)).collect();
+            assert_eq!(mle.evaluate_at_boolean(&bits), Some(mle[idx]));
+            assert_eq!(mle.evaluate_at_binary_field(&field_bits), mle[idx]);
+            assert_eq!(mle.evaluate(&field_bits), mle[idx]);
+        }
+        assert_eq!(mle.evaluate_at_boolean(&[true, false]), None);
+    }
+
+    #[test]
+    fn test_from_2d_table() {
+        let table: Vec<Vec<Fr>> = (0..4u64)
+            .map(|i| (0..4u64).map(|j| Fr::from(i * j)).collect())
+            .collect();
+        let mle = MultilinearExtension::from_2d_table(&table);
+        assert_eq!(mle.num_vars(), 4);
+
+        let to_bits = |x: u64| [(x >> 1) & 1 == 1, x & 1 == 1];
+        for i in 0..4 {
+            for j in 0..4 {
+                let bits = [to_bits(i), to_bits(j)].concat();
+                assert_eq!(mle.evaluate_at_boolean(&bits), Some(Fr::from(i * j)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_2d_table_rejects_ragged_rows() {
+        let table = vec![vec![Fr::ONE; 2], vec![Fr::ONE; 4]];
+        MultilinearExtension::from_2d_table(&table);
+    }
+
+    #[test]
+    fn test_affine_compose() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table.clone());
+        assert_eq!(mle.affine_compose(Fr::from(0), Fr::from(1)), mle);
+
+        let (low, high) = (Fr::from(3), Fr::from(10));
+        let composed = mle.affine_compose(low, high);
+        for (value, composed_value) in table.iter().zip(composed.table()) {
+            assert_eq!(*composed_value, low + Fr::from(7) * value);
+        }
+        let point: Vec<Fr> = (0..3).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let f = MultilinearExtension::new(vec![low, high]);
+        assert_eq!(
+            composed.evaluate(&point),
+            f.evaluate(&[mle.evaluate(&point)])
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_assert_equals_on_hypercube() {
+        let table: Vec<Fr> = (0..8).map(Fr::from).collect();
+        let mle = MultilinearExtension::new(table.clone());
+        assert_eq!(mle.assert_equals_on_hypercube(&mle.clone()), Ok(()));
+
+        let mut other = table;
+        other[5] = Fr::from(42);
+        other[6] = Fr::from(43);
+        assert_eq!(
+            mle.assert_equals_on_hypercube(&MultilinearExtension::new(other)),
+            Err((5, Fr::from(5), Fr::from(42)))
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_check_interpolation_consistency() {
+        let mut rng = test_rng();
+        for num_vars in 0..5 {
+            let table: Vec<Fr> = (0..(1 << num_vars))
+                .map(|_| Fr::from(rng.gen::<u64>()))
+                .collect();
+            let mle = MultilinearExtension::new(table);
+            for _ in 0..10 {
+                assert!(mle.check_interpolation_consistency(&mut rng));
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_lengths() {
+        for num_vars in 0..4 {
+            let mle = MultilinearExtension::new(vec![Fr::ONE; 1 << num_vars]);
+            assert_eq!(mle.table_len(), mle.full_table_len());
+            assert_eq!(mle.full_table_len(), 1 << num_vars);
+            assert_eq!(mle.is_empty(), num_vars == 0);
+        }
+
+        let padded = MultilinearExtension::from_vec_padded(vec![Fr::ONE; 5]);
+        assert!(padded.table_len() <= padded.full_table_len());
+        assert_eq!(padded.full_table_len(), 8);
+    }
+
+    #[test]
+    fn test_constant_constructors() {
+        let mut rng = test_rng();
+        for num_vars in 0..5 {
+            assert_eq!(
+                MultilinearExtension::<Fr>::zero(num_vars).sum_over_hypercube(),
+                Fr::ZERO
+            );
+            assert_eq!(
+                MultilinearExtension::<Fr>::one(num_vars).sum_over_hypercube(),
+                Fr::from(2u64.pow(num_vars as u32))
+            );
+
+            let c = Fr::random(&mut rng);
+            let constant = MultilinearExtension::constant(c, num_vars);
+            assert_eq!(constant.num_vars(), num_vars);
+            let point: Vec<Fr> = (0..num_vars).map(|_| Fr::random(&mut rng)).collect();
+            assert_eq!(constant.evaluate(&point), c);
+        }
+    }
+
+    #[test]
+    fn test_from_folding_sequence() {
+        let mut rng = test_rng();
+        let f = MultilinearExtension::new((0..8).map(|_| Fr::random(&mut rng)).collect());
+        let challenges: Vec<Fr> = (0..3).map(|_| Fr::random(&mut rng)).collect();
+        let sequence = MultilinearExtension::from_folding_sequence(f.table().to_vec(), &challenges);
+        assert_eq!(sequence.len(), 4);
+        assert_eq!(sequence[0], f);
+        for (i, stage) in sequence.iter().enumerate() {
+            assert_eq!(stage.num_vars(), 3 - i);
+        }
+        assert_eq!(sequence[3].table(), [f.evaluate(&challenges)]);
+
+        // The sum over the remaining variables after round i is the round
+        // polynomial \sum_b f(r_1, ..., r_{i - 1}, X, b) at r_i, which is linear.
+        for (stage, (next, &r)) in sequence.iter().zip(sequence[1..].iter().zip(&challenges)) {
+            let half = stage.table().len() / 2;
+            let at_zero: Fr = stage.table()[..half].iter().sum();
+            let at_one: Fr = stage.table()[half..].iter().sum();
+            assert_eq!(
+                next.sum_over_hypercube(),
+                (Fr::ONE - r) * at_zero + r * at_one
+            );
+        }
+    }
+
+    #[test]
+    fn test_kronecker_delta() {
+        let delta = MultilinearExtension::<Fr>::kronecker_delta(&[false, true, false]);
+        assert_eq!(delta.num_vars(), 3);
+        for idx in 0..8 {
+            let expected = if idx == 2 { Fr::ONE } else { Fr::ZERO };
+            assert_eq!(delta.get(idx), Some(expected));
+        }
+        assert_eq!(
+            MultilinearExtension::<Fr>::kronecker_delta(&[]).table(),
+            [Fr::ONE]
+        );
+    }
+
+    #[test]
+    fn test_bookkeeping_step() {
+        let mut rng = test_rng();
+        let mle = MultilinearExtension::new((0..16).map(|_| Fr::random(&mut rng)).collect());
+        let challenges: Vec<Fr> = (0..4).map(|_| Fr::random(&mut rng)).collect();
+
+        let mut checked = mle.table().to_vec();
+        let mut unchecked = mle.table().to_vec();
+        for &r in &challenges {
+            MultilinearExtension::bookkeeping_step(&mut checked, r);
+            MultilinearExtension::bookkeeping_step_unchecked(&mut unchecked, r);
+            assert_eq!(checked, unchecked);
+        }
+        assert_eq!(checked, vec![mle.evaluate(&challenges)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bookkeeping_step_on_constant() {
+        MultilinearExtension::bookkeeping_step(&mut vec![Fr::ONE], Fr::ONE);
+    }
+
+    #[test]
+    fn test_check_bookkeeping_invariant() {
+        let mut rng = test_rng();
+        let mle = MultilinearExtension::new((0..16).map(|_| Fr::random(&mut rng)).collect());
+        assert!(mle.check_bookkeeping_invariant(&[], mle.table()));
+
+        let mut table = mle.table().to_vec();
+        let mut challenges = vec![];
+        while table.len() > 1 {
+            challenges.push(Fr::random(&mut rng));
+            MultilinearExtension::restrict_first_var(&mut table, *challenges.last().unwrap());
+            assert!(mle.check_bookkeeping_invariant(&challenges, &table));
+        }
+        assert_eq!(table, vec![mle.evaluate(&challenges)]);
+
+        // A wrong entry, a wrong challenge or a wrong length is caught.
+        let mut table = mle.table().to_vec();
+        MultilinearExtension::restrict_first_var(&mut table, Fr::from(3));
+        assert!(!mle.check_bookkeeping_invariant(&[Fr::from(4)], &table));
+        assert!(!mle.check_bookkeeping_invariant(&[Fr::from(3)], &table[..4]));
+        table[5] += Fr::ONE;
+        assert!(!mle.check_bookkeeping_invariant(&[Fr::from(3)], &table));
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let mut mle = MultilinearExtension::new((0..8).map(Fr::from).collect());
+        assert_eq!(mle.as_slice(), mle.table());
+        assert_eq!(mle.as_slice().len(), mle.table_len());
+
+        for value in mle.as_mut_slice() {
+            *value = value.double();
+        }
+        assert_eq!(mle.as_mut_slice().len(), mle.table_len());
+        for idx in 0..8 {
+            assert_eq!(mle.get(idx), Some(Fr::from(2 * idx as u64)));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_on_coset() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table.clone());
+
+        // Every power of one stands for the point (1, 1, 1).
+        assert_eq!(mle.evaluate_on_coset(Fr::ONE), vec![table[7]; 8]);
+
+        let generator = Fr::from(rng.gen::<u64>());
+        let mut power = Fr::ONE;
+        for eval in mle.evaluate_on_coset(generator) {
+            let point = [power.square().square(), power.square(), power];
+            assert_eq!(eval, mle.evaluate(&point));
+            power *= generator;
+        }
+    }
+
+    #[test]
+    fn test_mle_equals() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..16).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table.clone());
+        assert!(MultilinearExtension::sample_at_random_point(
+            &mle,
+            &mle.clone(),
+            &mut rng
+        ));
+        assert!(MultilinearExtension::mle_equals(
+            &mle,
+            &mle.clone(),
+            &mut rng,
+            10
+        ));
+
+        let mut other = table;
+        other[9] += Fr::ONE;
+        let other = MultilinearExtension::new(other);
+        assert!(!MultilinearExtension::mle_equals(
+            &mle, &other, &mut rng, 10
+        ));
+
+        let fewer_vars = MultilinearExtension::new(mle.table()[..8].to_vec());
+        assert!(!MultilinearExtension::sample_at_random_point(
+            &mle,
+            &fewer_vars,
+            &mut rng
+        ));
+    }
+
+    #[test]
+    fn test_restrict_at_fixed_coords() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..16).map(|_| Fr::random(&mut rng)).collect();
+        let mle = MultilinearExtension::new(table);
+        let point: Vec<Fr> = (0..4).map(|_| Fr::random(&mut rng)).collect();
+
+        let all: Vec<(usize, Fr)> = [2, 0, 3, 1].map(|var| (var, point[var])).to_vec();
+        let restricted = mle.restrict_at_fixed_coords(&all);
+        assert_eq!(restricted.num_vars(), 0);
+        assert_eq!(restricted.table(), &[mle.evaluate(&point)]);
+
+        // Fixing x_0 and x_2 leaves an MLE over (x_1, x_3).
+        let restricted = mle.restrict_at_fixed_coords(&[(2, point[2]), (0, point[0])]);
+        assert_eq!(
+            restricted.evaluate(&[point[1], point[3]]),
+            mle.evaluate(&point)
+        );
+        assert_eq!(mle.restrict_at_fixed_coords(&[]), mle);
+    }
+
+    #[test]
+    fn test_eq_weighted_sum() {
+        let mut rng = test_rng();
+        for num_vars in 0..5 {
+            let table: Vec<Fr> = (0..(1 << num_vars))
+                .map(|_| Fr::from(rng.gen::<u64>()))
+                .collect();
+            let mle = MultilinearExtension::new(table);
+            for _ in 0..50 {
+                let alpha: Vec<Fr> = (0..num_vars).map(|_| Fr::random(&mut rng)).collect();
+                assert_eq!(mle.eq_weighted_sum(&alpha), mle.evaluate(&alpha));
+                assert_eq!(
+                    mle.eq_weighted_sum(&alpha),
+                    evaluate_over_hypercube(&mle, &alpha)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inclusion_exclusion_transform() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table);
+
+        // f(r) = \sum_b c_b \prod_{i : b_i = 1} r_i.
+        let coeffs = mle.inclusion_exclusion_transform(0b111);
+        let point: Vec<Fr> = (0..3).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let eval = (0..8).fold(Fr::ZERO, |acc, b| {
+            let monomial = (0..3)
+                .filter(|i| (b >> (2 - i)) & 1 == 1)
+                .fold(Fr::ONE, |acc, i| acc * point[i]);
+            acc + coeffs[b] * monomial
+        });
+        assert_eq!(eval, mle.evaluate(&point));
+
+        // Transforms over disjoint sets of variables compose.
+        let partial = mle.inclusion_exclusion_transform(0b001);
+        assert_eq!(partial.inclusion_exclusion_transform(0b110), coeffs);
+        assert_eq!(mle.inclusion_exclusion_transform(0), mle);
+    }
+
+    #[test]
+    fn test_sample_multilinearity() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..16).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        assert!(MultilinearExtension::new(table.clone()).sample_multilinearity(&mut rng, 10));
+
+        // Swapping two entries gives a different, but still multilinear,
+        // polynomial.
+        let mut swapped = table;
+        swapped.swap(3, 12);
+        assert!(MultilinearExtension::new(swapped).sample_multilinearity(&mut rng, 10));
+
+        let constant = MultilinearExtension::new(vec![Fr::from(5)]);
+        assert!(constant.sample_multilinearity(&mut rng, 3));
+    }
+
+    #[test]
+    fn test_sum_over_subset() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table.clone());
+
+        for idx in 0..8 {
+            let mask: Vec<bool> = (0..8).map(|i| i == idx).collect();
+            assert_eq!(mle.sum_over_subset(&mask), mle.get(idx).unwrap());
+        }
+        assert_eq!(
+            mle.sum_over_subset(&[true; 8]),
+            table.iter().fold(Fr::ZERO, |acc, x| acc + x)
+        );
+        assert_eq!(mle.sum_over_subset(&[false; 8]), Fr::ZERO);
+    }
+
+    #[test]
+    fn test_partial_sum_over_last_k_vars() {
+        let table: Vec<Fr> = (0..8).map(Fr::from).collect();
+        let mle = MultilinearExtension::new(table.clone());
+        assert_eq!(mle.partial_sum_over_last_k_vars(0), mle);
+
+        let partial = mle.partial_sum_over_last_k_vars(1);
+        assert_eq!(partial.num_vars(), 2);
+        let expected: Vec<Fr> = [1, 5, 9, 13].into_iter().map(Fr::from).collect();
+        assert_eq!(partial.table(), &expected[..]);
+
+        let total = mle.partial_sum_over_last_k_vars(3);
+        assert_eq!(total.num_vars(), 0);
+        assert_eq!(total[0], table.iter().fold(Fr::ZERO, |acc, x| acc + x));
+    }
+
+    #[test]
+    fn test_map() {
+        let mut rng = test_rng();
+        let table: Vec<Fr> = (0..8).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        let mle = MultilinearExtension::new(table);
+
+        // Adding one to every entry adds the constant MLE one.
+        let shifted = mle.map(|x| x + Fr::ONE);
+        let point: Vec<Fr> = (0..3).map(|_| Fr::from(rng.gen::<u64>())).collect();
+        assert_eq!(shifted.evaluate(&point), mle.evaluate(&point) + Fr::ONE);
+
+        let squared = mle.map(|x| x * x);
+        for (value, squared_value) in mle.table().iter().zip(squared.table()) {
+            assert_eq!(*squared_value, value.square());
+        }
+
+        let indexed = mle.map_enumerate(|idx, x| x * Fr::from(idx as u64));
+        for idx in 0..8 {
+            assert_eq!(indexed[idx], mle[idx] * Fr::from(idx as u64));
+        }
+    }
 }
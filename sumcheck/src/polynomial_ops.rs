@@ -0,0 +1,160 @@
+//! Standalone operations on univariate polynomials given as
+//! [UnivariateEvals]. Results are stored with as many evaluations as their
+//! degree bound requires, except for [poly_gcd], whose result is trimmed to its
+//! actual degree.
+
+use crate::utils::UnivariateEvals;
+use shared_types::Field;
+
+/// The coefficients of `f` without trailing zeros, so that the zero polynomial
+/// has none.
+fn trimmed_coefficients<F: Field>(f: &UnivariateEvals<F>) -> Vec<F> {
+    let mut coeffs = f.to_coefficient_vec();
+    while coeffs.last() == Some(&F::ZERO) {
+        coeffs.pop();
+    }
+    coeffs
+}
+
+/// Evaluates `num_evals` points 0, 1, ... of the polynomial given by `eval`.
+fn tabulate<F: Field>(num_evals: usize, eval: impl Fn(F) -> F) -> UnivariateEvals<F> {
+    UnivariateEvals::new((0..num_evals).map(|x| eval(F::from(x as u64))).collect())
+}
+
+/// Returns f * g, of degree `deg(f) + deg(g)`.
+pub fn poly_multiply<F: Field>(
+    f: &UnivariateEvals<F>,
+    g: &UnivariateEvals<F>,
+) -> UnivariateEvals<F> {
+    tabulate(f.get_degree() + g.get_degree() + 1, |x| {
+        f.evaluate_at_a_point(x).unwrap() * g.evaluate_at_a_point(x).unwrap()
+    })
+}
+
+/// Returns the monic greatest common divisor of `f` and `g`, via the Euclidean
+/// algorithm in coefficient form. The gcd of two zero polynomials is zero.
+pub fn poly_gcd<F: Field>(f: &UnivariateEvals<F>, g: &UnivariateEvals<F>) -> UnivariateEvals<F> {
+    let (mut a, mut b) = (trimmed_coefficients(f), trimmed_coefficients(g));
+    while let Some(&lead) = b.last() {
+        // Reduce a modulo b, one leading term at a time.
+        let lead_inv = lead.invert().unwrap();
+        while a.len() >= b.len() {
+            let factor = *a.last().unwrap() * lead_inv;
+            let shift = a.len() - b.len();
+            for (a_coeff, b_coeff) in a[shift..].iter_mut().zip(&b) {
+                *a_coeff -= factor * b_coeff;
+            }
+            a.pop();
+            while a.last() == Some(&F::ZERO) {
+                a.pop();
+            }
+        }
+        std::mem::swap(&mut a, &mut b);
+    }
+    if let Some(&lead) = a.last() {
+        let lead_inv = lead.invert().unwrap();
+        a.iter_mut().for_each(|coeff| *coeff *= lead_inv);
+    }
+    UnivariateEvals::from_coefficients(&a)
+}
+
+/// Returns `outer`(`inner`(x)), of degree `deg(outer) * deg(inner)`.
+pub fn poly_compose<F: Field>(
+    outer: &UnivariateEvals<F>,
+    inner: &UnivariateEvals<F>,
+) -> UnivariateEvals<F> {
+    tabulate(outer.get_degree() * inner.get_degree() + 1, |x| {
+        outer
+            .evaluate_at_a_point(inner.evaluate_at_a_point(x).unwrap())
+            .unwrap()
+    })
+}
+
+/// Returns the unique polynomial of degree less than `points.len()` passing
+/// through every (x, y) in `points`, by Lagrange interpolation. Panics if
+/// `points` is empty or two points share an x-coordinate.
+pub fn poly_interpolate<F: Field>(points: &[(F, F)]) -> UnivariateEvals<F> {
+    assert!(!points.is_empty());
+    tabulate(points.len(), |x| {
+        points
+            .iter()
+            .enumerate()
+            .fold(F::ZERO, |acc, (i, &(x_i, y_i))| {
+                let (num, denom) = points
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .fold((F::ONE, F::ONE), |(num, denom), (_, &(x_j, _))| {
+                        (num * (x - x_j), denom * (x_i - x_j))
+                    });
+                acc + y_i * num * denom.invert().unwrap()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        polynomial_ops::{poly_compose, poly_gcd, poly_interpolate, poly_multiply},
+        utils::UnivariateEvals,
+    };
+    use shared_types::{ff_field, Fr};
+
+    fn poly(evals: &[i64]) -> UnivariateEvals<Fr> {
+        UnivariateEvals::new(
+            evals
+                .iter()
+                .map(|&eval| {
+                    if eval < 0 {
+                        -Fr::from(eval.unsigned_abs())
+                    } else {
+                        Fr::from(eval as u64)
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_poly_gcd() {
+        // x^2 - 1 and x - 1.
+        let f = poly(&[-1, 0, 3]);
+        let g = poly(&[-1, 0]);
+        assert_eq!(poly_gcd(&f, &g), g);
+        assert_eq!(poly_gcd(&g, &f), g);
+
+        // x^2 + 1 and x are coprime.
+        assert_eq!(poly_gcd(&poly(&[1, 2, 5]), &poly(&[0, 1])), poly(&[1]));
+        assert_eq!(poly_gcd(&poly(&[0]), &poly(&[0])), poly(&[0]));
+    }
+
+    #[test]
+    fn test_poly_multiply_and_compose() {
+        // (x + 1) * (x - 1) = x^2 - 1.
+        assert_eq!(
+            poly_multiply(&poly(&[1, 2]), &poly(&[-1, 0])),
+            poly(&[-1, 0, 3])
+        );
+
+        // (x^2 - 1) composed with (x + 1) is x^2 + 2x.
+        let composed = poly_compose(&poly(&[-1, 0, 3]), &poly(&[1, 2]));
+        assert_eq!(composed, poly(&[0, 3, 8]));
+    }
+
+    #[test]
+    fn test_poly_interpolate() {
+        let f = poly(&[-1, 0, 3]);
+        let points: Vec<(Fr, Fr)> = [5, 7, 11]
+            .into_iter()
+            .map(|x| {
+                let x = Fr::from(x);
+                (x, f.evaluate_at_a_point(x).unwrap())
+            })
+            .collect();
+        assert_eq!(poly_interpolate(&points), f);
+        assert_eq!(
+            poly_interpolate(&[(Fr::from(3), Fr::ONE)]),
+            UnivariateEvals::new(vec![Fr::ONE])
+        );
+    }
+}
@@ -1,13 +1,35 @@
 use anyhow::{anyhow, Result};
-use shared_types::Field;
+use ark_std::rand::RngCore;
+use shared_types::{
+    transcript::{fiat_shamir::FiatShamir, TranscriptSponge},
+    Field,
+};
 
 /// A sumcheck proof consists of:
 /// * Prover-claimed sum over the hypercube, i.e. \sum_{b_1, ..., b_n} f(b_1, ..., b_n)
 /// * Univariate polynomial \sum_{b_{i + 1}, ..., b_n} f(r_1, ..., r_{i - 1}, X, b_{i + 1}, ..., b_n)
-/// for the ith round.
+///   for the ith round.
+///
+/// A proof built by [SumcheckProof::merge] also carries the claimed sums of
+/// its two parts, which [SumcheckProof::split] checks against the merged one.
+///
+/// Optionally, a proof carries human-readable metadata for debugging, e.g.
+/// which layer of a GKR circuit it belongs to. The metadata is not part of
+/// the proof: it is ignored by equality and never absorbed into a transcript.
+#[derive(Debug)]
 pub struct SumcheckProof<F: Field> {
     claimed_sum: F,
     prover_sumcheck_round_messages: Vec<UnivariateEvals<F>>,
+    component_claims: Option<(F, F)>,
+    metadata: Option<String>,
+}
+
+impl<F: Field> PartialEq for SumcheckProof<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.claimed_sum == other.claimed_sum
+            && self.prover_sumcheck_round_messages == other.prover_sumcheck_round_messages
+            && self.component_claims == other.component_claims
+    }
 }
 
 impl<F: Field> SumcheckProof<F> {
@@ -15,21 +37,154 @@ impl<F: Field> SumcheckProof<F> {
         Self {
             claimed_sum,
             prover_sumcheck_round_messages,
+            component_claims: None,
+            metadata: None,
         }
     }
 
+    /// Attaches debugging metadata, e.g. "layer 3 of a depth-5 GKR circuit".
+    pub fn with_metadata(mut self, s: impl Into<String>) -> Self {
+        self.metadata = Some(s.into());
+        self
+    }
+
+    pub fn get_metadata(&self) -> Option<&str> {
+        self.metadata.as_deref()
+    }
+
     pub fn get_claimed_sum(&self) -> F {
         self.claimed_sum
     }
 
+    /// The claimed sums of the two parts of a proof built by
+    /// [SumcheckProof::merge], or `None` for any other proof.
+    pub fn get_component_claims(&self) -> Option<(F, F)> {
+        self.component_claims
+    }
+
+    #[deprecated(note = "Use `rounds` or `get_round_message`, which do not clone")]
     pub fn get_prover_sumcheck_round_messages(&self) -> Vec<UnivariateEvals<F>> {
         self.prover_sumcheck_round_messages.clone()
     }
+
+    pub fn num_rounds(&self) -> usize {
+        self.prover_sumcheck_round_messages.len()
+    }
+
+    /// The prover's message for each round.
+    pub fn rounds(&self) -> &[UnivariateEvals<F>] {
+        &self.prover_sumcheck_round_messages
+    }
+
+    /// The prover's message for `round`, if the proof has that many rounds.
+    pub fn get_round_message(&self, round: usize) -> Option<&UnivariateEvals<F>> {
+        self.prover_sumcheck_round_messages.get(round)
+    }
+
+    /// Combines the proofs of two sumcheck instances over disjoint sets of
+    /// variables into one proof with `first.num_rounds() + second.num_rounds()`
+    /// rounds, whose claimed sum is the product of the two claimed sums, i.e.
+    /// the sum of f(x) * g(y) over (x, y). Both claimed sums are kept as the
+    /// merged proof's component claims. The round messages are concatenated
+    /// unchanged, so each part keeps the challenges of its own transcript and
+    /// the merged proof is verified by [SumcheckProof::split]ting it again.
+    /// Metadata is dropped. Panics if either proof has no rounds.
+    pub fn merge(first: SumcheckProof<F>, second: SumcheckProof<F>) -> SumcheckProof<F> {
+        assert!(first.num_rounds() > 0 && second.num_rounds() > 0);
+        let mut rounds = first.prover_sumcheck_round_messages;
+        rounds.extend(second.prover_sumcheck_round_messages);
+        let mut merged = SumcheckProof::new(first.claimed_sum * second.claimed_sum, rounds);
+        merged.component_claims = Some((first.claimed_sum, second.claimed_sum));
+        merged
+    }
+
+    /// Inverse of [SumcheckProof::merge]: splits `proof` into its first
+    /// `split_point` rounds and the rest, claiming its two component claims.
+    /// Returns an error unless `proof` has component claims whose product is
+    /// its claimed sum, so that verifying both parts also verifies the merged
+    /// claim. Panics unless both parts have at least one round.
+    pub fn split(
+        proof: SumcheckProof<F>,
+        split_point: usize,
+    ) -> Result<(SumcheckProof<F>, SumcheckProof<F>)> {
+        assert!(0 < split_point && split_point < proof.num_rounds());
+        let (first_claim, second_claim) = proof
+            .component_claims
+            .ok_or(anyhow!("Proof was not built by merging two proofs"))?;
+        if first_claim * second_claim != proof.claimed_sum {
+            return Err(anyhow!(
+                "Component claims do not multiply to the merged claimed sum"
+            ));
+        }
+        let mut first_rounds = proof.prover_sumcheck_round_messages;
+        let second_rounds = first_rounds.split_off(split_point);
+        Ok((
+            SumcheckProof::new(first_claim, first_rounds),
+            SumcheckProof::new(second_claim, second_rounds),
+        ))
+    }
+}
+
+/// Iterates over the prover's round messages, in order.
+impl<'a, F: Field> IntoIterator for &'a SumcheckProof<F> {
+    type Item = &'a UnivariateEvals<F>;
+    type IntoIter = std::slice::Iter<'a, UnivariateEvals<F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.prover_sumcheck_round_messages.iter()
+    }
+}
+
+/// Starts from an existing [SumcheckProof] (typically an honest one) and
+/// replaces individual parts of it, e.g. to construct adversarial proofs in
+/// soundness tests.
+pub struct SumcheckProofBuilder<F: Field> {
+    claimed_sum: F,
+    prover_sumcheck_round_messages: Vec<UnivariateEvals<F>>,
+    component_claims: Option<(F, F)>,
+    metadata: Option<String>,
+}
+
+impl<F: Field> SumcheckProofBuilder<F> {
+    pub fn from_proof(proof: SumcheckProof<F>) -> Self {
+        Self {
+            claimed_sum: proof.claimed_sum,
+            prover_sumcheck_round_messages: proof.prover_sumcheck_round_messages,
+            component_claims: proof.component_claims,
+            metadata: proof.metadata,
+        }
+    }
+
+    pub fn with_claimed_sum(mut self, s: F) -> Self {
+        self.claimed_sum = s;
+        self
+    }
+
+    pub fn with_component_claims(mut self, first: F, second: F) -> Self {
+        self.component_claims = Some((first, second));
+        self
+    }
+
+    /// Replaces the prover's message for round `i`. Panics if the proof has no
+    /// such round.
+    pub fn with_round_message(mut self, i: usize, msg: UnivariateEvals<F>) -> Self {
+        self.prover_sumcheck_round_messages[i] = msg;
+        self
+    }
+
+    pub fn build(self) -> SumcheckProof<F> {
+        SumcheckProof {
+            claimed_sum: self.claimed_sum,
+            prover_sumcheck_round_messages: self.prover_sumcheck_round_messages,
+            component_claims: self.component_claims,
+            metadata: self.metadata,
+        }
+    }
 }
 
 /// Basic structure of a univariate polynomial, as defined by its evaluations
 /// f(0), f(1), ..., f(d) for a degree-d polynomial.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct UnivariateEvals<F: Field> {
     evals: Vec<F>,
     univariate_poly_deg: usize,
@@ -38,13 +193,58 @@ pub struct UnivariateEvals<F: Field> {
 impl<F: Field> UnivariateEvals<F> {
     /// Constructor. Will automatically infer the polynomial degree.
     pub fn new(evals: Vec<F>) -> Self {
-        assert!(evals.len() > 0);
+        assert!(!evals.is_empty());
         Self {
             univariate_poly_deg: evals.len() - 1,
             evals,
         }
     }
 
+    /// A random polynomial of degree `degree`, given by uniformly random
+    /// evaluations at 0, 1, ..., `degree`.
+    pub fn random(degree: usize, rng: &mut impl RngCore) -> Self {
+        Self::new((0..=degree).map(|_| F::random(&mut *rng)).collect())
+    }
+
+    /// A random polynomial of degree `degree` with f(0) + f(1) = 0, e.g. for
+    /// faking a round message in soundness tests. f(0) is random, f(1) = -f(0)
+    /// and the remaining evaluations are random. Panics if `degree` is zero.
+    pub fn random_with_sum_zero(degree: usize, rng: &mut impl RngCore) -> Self {
+        assert!(degree > 0);
+        let mut evals: Vec<F> = (0..=degree).map(|_| F::random(&mut *rng)).collect();
+        evals[1] = -evals[0];
+        Self::new(evals)
+    }
+
+    /// `n` random round messages of degree `degree` which pass every round
+    /// check of a sumcheck verifier starting from `claimed_sum`: the first has
+    /// f(0) + f(1) = `claimed_sum`, and each later one sums to the previous
+    /// message evaluated at its challenge. Challenges are squeezed from
+    /// `transcript` exactly as the verifier would, so a verifier starting from
+    /// the same transcript state derives the same ones. Panics if `degree` is
+    /// zero.
+    pub fn random_sequence_consistent(
+        n: usize,
+        degree: usize,
+        claimed_sum: F,
+        transcript: &mut impl TranscriptSponge<F>,
+        rng: &mut impl RngCore,
+    ) -> Vec<Self> {
+        let mut fiat_shamir = FiatShamir::new(transcript);
+        fiat_shamir.absorb_prover_message(&[claimed_sum]);
+        let mut expected = claimed_sum;
+        (0..n)
+            .map(|_| {
+                let mut msg = Self::random_with_sum_zero(degree, &mut *rng);
+                msg.evals[1] += expected;
+                fiat_shamir.absorb_prover_message(&msg.evals);
+                let r_i = fiat_shamir.get_verifier_challenge();
+                expected = msg.evaluate_at_a_point(r_i).unwrap();
+                msg
+            })
+            .collect()
+    }
+
     pub fn get_raw_evals(&self) -> Vec<F> {
         self.evals.clone()
     }
@@ -53,6 +253,140 @@ impl<F: Field> UnivariateEvals<F> {
         self.univariate_poly_deg
     }
 
+    /// f(0), read off directly without interpolating.
+    pub fn evaluate_at_zero(&self) -> F {
+        self.evals[0]
+    }
+
+    /// f(1), read off directly without interpolating. For a constant
+    /// polynomial this is f(0).
+    pub fn evaluate_at_one(&self) -> F {
+        *self.evals.get(1).unwrap_or(&self.evals[0])
+    }
+
+    /// Whether f(0) + f(1) = `expected`, i.e. the sumcheck verifier's round
+    /// check against the previous round's evaluation.
+    pub fn is_consistent_with_partial_sum(&self, expected: F) -> bool {
+        self.evaluate_at_zero() + self.evaluate_at_one() == expected
+    }
+
+    /// Whether the polynomial actually has degree at most `max_degree`, no
+    /// matter how many evaluations it is stored with.
+    pub fn check_degree_bound(&self, max_degree: usize) -> bool {
+        self.to_coefficient_vec()
+            .iter()
+            .skip(max_degree + 1)
+            .all(|coeff| *coeff == F::ZERO)
+    }
+
+    /// Treats the evaluations as an overdetermined message for a polynomial of
+    /// degree d = len - 2, and checks that the extra (d+1)-st evaluation agrees
+    /// with the first d + 1. The interpolants through the first d + 1 and through
+    /// all d + 2 evaluations are compared at a challenge `rho` squeezed from
+    /// `transcript`; they differ everywhere but at most d + 1 points unless the
+    /// extra evaluation is consistent. The evaluations should already have been
+    /// absorbed into `transcript`. Messages with fewer than two evaluations
+    /// carry nothing extra and pass trivially.
+    pub fn check_low_degree(&self, transcript: &mut impl TranscriptSponge<F>) -> bool {
+        if self.evals.len() < 2 {
+            return true;
+        }
+        let rho = transcript.squeeze();
+        let determined = UnivariateEvals::new(self.evals[..self.evals.len() - 1].to_vec());
+        match (
+            determined.evaluate_at_a_point(rho),
+            self.evaluate_at_a_point(rho),
+        ) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            _ => false,
+        }
+    }
+
+    /// Returns the same polynomial, stored with only as many evaluations as its
+    /// actual degree requires. Note that sumcheck round messages must keep their
+    /// claimed degree, so this is meant for diagnostics rather than for trimming
+    /// prover messages.
+    pub fn trim_to_degree(&self) -> UnivariateEvals<F> {
+        let degree = self
+            .to_coefficient_vec()
+            .iter()
+            .rposition(|coeff| *coeff != F::ZERO)
+            .unwrap_or(0);
+        UnivariateEvals::new(self.evals[..=degree].to_vec())
+    }
+
+    /// Whether `combined` = \sum_i `coefficients[i]` * `per_instance[i]`, i.e. the
+    /// consistency check of a batched sumcheck's combined round message. The
+    /// polynomials may have different degrees; they are compared at enough
+    /// points to pin down the largest one.
+    pub fn split_linear_combination(
+        combined: &UnivariateEvals<F>,
+        coefficients: &[F],
+        per_instance: &[UnivariateEvals<F>],
+    ) -> bool {
+        if coefficients.len() != per_instance.len() {
+            return false;
+        }
+        let degree = per_instance
+            .iter()
+            .map(|poly| poly.get_degree())
+            .fold(combined.get_degree(), usize::max);
+        (0..=degree).all(|x| {
+            let x = F::from(x as u64);
+            let expected = coefficients
+                .iter()
+                .zip(per_instance)
+                .fold(F::ZERO, |acc, (coeff, poly)| {
+                    acc + *coeff * poly.evaluate_at_a_point(x).unwrap()
+                });
+            combined.evaluate_at_a_point(x).unwrap() == expected
+        })
+    }
+
+    /// The polynomial \sum_i c_i * x^i with coefficients `coeffs` = [c_0, ...],
+    /// stored by its evaluations at 0, 1, ..., `coeffs.len()` - 1 (or just 0
+    /// for no coefficients). Inverse of [UnivariateEvals::to_coefficient_vec].
+    pub fn from_coefficients(coeffs: &[F]) -> Self {
+        let evals = (0..coeffs.len().max(1))
+            .map(|x| {
+                let x = F::from(x as u64);
+                coeffs
+                    .iter()
+                    .rev()
+                    .fold(F::ZERO, |acc, coeff| acc * x + coeff)
+            })
+            .collect();
+        Self::new(evals)
+    }
+
+    /// Coefficients [c_0, ..., c_d] of the polynomial, by expanding each
+    /// Lagrange basis polynomial over the points 0, 1, ..., d. Trailing
+    /// coefficients are zero if the polynomial's actual degree is below d.
+    pub fn to_coefficient_vec(&self) -> Vec<F> {
+        let num_evals = self.evals.len();
+        let mut coeffs = vec![F::ZERO; num_evals];
+        for (i, eval) in self.evals.iter().enumerate() {
+            // \prod_{j != i} (X - j), starting from the constant polynomial 1.
+            let mut basis = vec![F::ONE];
+            let mut denom = F::ONE;
+            for j in (0..num_evals).filter(|&j| j != i) {
+                let root = F::from(j as u64);
+                let mut next = vec![F::ZERO; basis.len() + 1];
+                for (k, coeff) in basis.iter().enumerate() {
+                    next[k + 1] += coeff;
+                    next[k] -= *coeff * root;
+                }
+                basis = next;
+                denom *= F::from(i as u64) - root;
+            }
+            let scale = *eval * denom.invert().unwrap();
+            for (coeff, basis_coeff) in coeffs.iter_mut().zip(basis) {
+                *coeff += scale * basis_coeff;
+            }
+        }
+        coeffs
+    }
+
     /// Use degree + 1 evaluations to figure out the evaluation at some arbitrary
     /// point
     pub fn evaluate_at_a_point(&self, point: F) -> Result<F> {
@@ -65,10 +399,10 @@ impl<F: Field> UnivariateEvals<F> {
 
         // Special cases for `point == 0` and `point == 1`.
         if point == F::ZERO {
-            return Ok(self.evals[0]);
+            return Ok(self.evaluate_at_zero());
         }
         if point == F::ONE {
-            return Ok(*self.evals.get(1).unwrap_or(&self.evals[0]));
+            return Ok(self.evaluate_at_one());
         }
 
         // Need degree + 1 evaluations to interpolate
@@ -97,3 +431,308 @@ impl<F: Field> UnivariateEvals<F> {
         eval.ok_or(anyhow!("Interpretation Error: No Inverse"))
     }
 }
+
+/// A univariate polynomial \sum_i c_i * x^i given by its coefficients
+/// [c_0, c_1, ..., c_d], as opposed to its evaluations as in [UnivariateEvals].
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnivariateCoeffPoly<F: Field> {
+    coeffs: Vec<F>,
+}
+
+impl<F: Field> UnivariateCoeffPoly<F> {
+    /// Constructor. As with [UnivariateEvals], the degree is inferred from the
+    /// number of coefficients, which must be positive.
+    pub fn new(coeffs: Vec<F>) -> Self {
+        assert!(!coeffs.is_empty());
+        Self { coeffs }
+    }
+
+    pub fn coeffs(&self) -> &[F] {
+        &self.coeffs
+    }
+
+    /// The number of coefficients minus one. As with
+    /// [UnivariateEvals::get_degree], this is an upper bound: trailing
+    /// coefficients may be zero.
+    pub fn degree(&self) -> usize {
+        self.coeffs.len() - 1
+    }
+
+    /// f(`x`), by Horner's method.
+    pub fn evaluate_at(&self, x: F) -> F {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, coeff| acc * x + coeff)
+    }
+
+    /// Interpolates the coefficients of the polynomial given by `evals`.
+    pub fn from_evals(evals: &UnivariateEvals<F>) -> Self {
+        Self::new(evals.to_coefficient_vec())
+    }
+
+    /// The evaluations at 0, 1, ..., `num_points` - 1, which determine the
+    /// polynomial if `num_points` exceeds its degree. Panics if `num_points` is
+    /// zero.
+    pub fn to_evals(&self, num_points: usize) -> UnivariateEvals<F> {
+        UnivariateEvals::new(
+            (0..num_points)
+                .map(|x| self.evaluate_at(F::from(x as u64)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{SumcheckProof, UnivariateCoeffPoly, UnivariateEvals};
+    use ark_std::test_rng;
+    use shared_types::{ff_field, transcript::poseidon_sponge::PoseidonSponge, Fr};
+
+    #[test]
+    fn test_round_checks() {
+        // 1 + x + x^2, with f(0) + f(1) = 4.
+        let evals: Vec<Fr> = [1, 3, 7].into_iter().map(Fr::from).collect();
+        let poly = UnivariateEvals::new(evals);
+        assert!(poly.is_consistent_with_partial_sum(Fr::from(4)));
+        assert!(!poly.is_consistent_with_partial_sum(Fr::from(5)));
+        assert!(poly.check_degree_bound(2));
+        assert!(!poly.check_degree_bound(1));
+
+        // 2 + 3x, padded to four evaluations, is within a degree bound of one.
+        let evals: Vec<Fr> = [2, 5, 8, 11].into_iter().map(Fr::from).collect();
+        assert!(UnivariateEvals::new(evals).check_degree_bound(1));
+
+        let constant = UnivariateEvals::new(vec![Fr::from(3)]);
+        assert!(constant.is_consistent_with_partial_sum(Fr::from(6)));
+    }
+
+    #[test]
+    fn test_check_low_degree() {
+        let mut rng = test_rng();
+        for degree in 1..5 {
+            // Degree `degree` polynomial, sent with one evaluation too many.
+            let coeffs: Vec<Fr> = (0..=degree).map(|_| Fr::random(&mut rng)).collect();
+            let consistent =
+                UnivariateEvals::from_coefficients(&[coeffs.clone(), vec![Fr::ZERO]].concat());
+            assert_eq!(consistent.get_raw_evals().len(), degree + 2);
+            assert!(consistent.check_low_degree(&mut PoseidonSponge::default()));
+
+            let mut evals = consistent.get_raw_evals();
+            *evals.last_mut().unwrap() += Fr::ONE;
+            let inconsistent = UnivariateEvals::new(evals);
+            assert!(!inconsistent.check_low_degree(&mut PoseidonSponge::default()));
+        }
+        assert!(UnivariateEvals::new(vec![Fr::from(7)])
+            .check_low_degree(&mut PoseidonSponge::default()));
+    }
+
+    #[test]
+    fn test_split_linear_combination() {
+        let mut rng = test_rng();
+        let per_instance: Vec<UnivariateEvals<Fr>> = (1..4)
+            .map(|degree| UnivariateEvals::random(degree, &mut rng))
+            .collect();
+        let coefficients: Vec<Fr> = (0..3).map(|_| Fr::random(&mut rng)).collect();
+        let combined_evals: Vec<Fr> = (0..4)
+            .map(|x| {
+                let x = Fr::from(x as u64);
+                coefficients
+                    .iter()
+                    .zip(&per_instance)
+                    .fold(Fr::ZERO, |acc, (coeff, poly)| {
+                        acc + *coeff * poly.evaluate_at_a_point(x).unwrap()
+                    })
+            })
+            .collect();
+
+        let combined = UnivariateEvals::new(combined_evals.clone());
+        assert!(UnivariateEvals::split_linear_combination(
+            &combined,
+            &coefficients,
+            &per_instance
+        ));
+
+        let mut tampered = combined_evals;
+        tampered[3] += Fr::ONE;
+        assert!(!UnivariateEvals::split_linear_combination(
+            &UnivariateEvals::new(tampered),
+            &coefficients,
+            &per_instance
+        ));
+        assert!(!UnivariateEvals::split_linear_combination(
+            &combined,
+            &coefficients[..2],
+            &per_instance
+        ));
+    }
+
+    #[test]
+    fn test_coefficient_round_trip() {
+        let mut rng = test_rng();
+        for degree in 1..=8 {
+            let poly = UnivariateEvals::<Fr>::random(degree, &mut rng);
+            let coeffs = poly.to_coefficient_vec();
+            assert_eq!(coeffs.len(), degree + 1);
+            assert_eq!(UnivariateEvals::from_coefficients(&coeffs), poly);
+        }
+
+        // 2 + 3x + x^2
+        let coeffs: Vec<Fr> = [2, 3, 1].into_iter().map(Fr::from).collect();
+        let poly = UnivariateEvals::from_coefficients(&coeffs);
+        assert_eq!(poly.get_raw_evals(), [2, 6, 12].map(Fr::from));
+        assert_eq!(poly.to_coefficient_vec(), coeffs);
+    }
+
+    #[test]
+    fn test_merge_split_round_trip() {
+        let mut rng = test_rng();
+        let mut transcript = PoseidonSponge::default();
+        let (first_sum, second_sum) = (Fr::from(3), Fr::from(5));
+        let first_rounds =
+            UnivariateEvals::random_sequence_consistent(3, 2, first_sum, &mut transcript, &mut rng);
+        let second_rounds = UnivariateEvals::random_sequence_consistent(
+            2,
+            2,
+            second_sum,
+            &mut transcript,
+            &mut rng,
+        );
+
+        let merged = SumcheckProof::merge(
+            SumcheckProof::new(first_sum, first_rounds.clone()),
+            SumcheckProof::new(second_sum, second_rounds.clone()),
+        );
+        assert_eq!(merged.num_rounds(), 5);
+        assert_eq!(merged.get_claimed_sum(), Fr::from(15));
+
+        assert_eq!(merged.get_component_claims(), Some((first_sum, second_sum)));
+
+        let (first, second) = SumcheckProof::split(merged, 3).unwrap();
+        assert_eq!(first, SumcheckProof::new(first_sum, first_rounds));
+        assert_eq!(second, SumcheckProof::new(second_sum, second_rounds));
+    }
+
+    #[test]
+    fn test_coeff_poly_conversions() {
+        let mut rng = test_rng();
+        for degree in 0..=6 {
+            let evals = UnivariateEvals::<Fr>::random(degree, &mut rng);
+            let poly = UnivariateCoeffPoly::from_evals(&evals);
+            assert_eq!(poly.degree(), degree);
+            assert_eq!(poly.to_evals(degree + 1), evals);
+            assert_eq!(
+                UnivariateCoeffPoly::from_evals(&poly.to_evals(degree + 1)),
+                poly
+            );
+
+            for _ in 0..4 {
+                let x = Fr::random(&mut rng);
+                assert_eq!(poly.evaluate_at(x), evals.evaluate_at_a_point(x).unwrap());
+            }
+        }
+
+        // 2 + 3x + x^2
+        let poly = UnivariateCoeffPoly::new([2, 3, 1].map(Fr::from).to_vec());
+        assert_eq!(poly.evaluate_at(Fr::from(5)), Fr::from(42));
+        assert_eq!(
+            poly.to_evals(4).get_raw_evals(),
+            [2, 6, 12, 20].map(Fr::from)
+        );
+    }
+
+    #[test]
+    fn test_round_accessors() {
+        let mut rng = test_rng();
+        let messages: Vec<UnivariateEvals<Fr>> = (0..3)
+            .map(|_| UnivariateEvals::random(2, &mut rng))
+            .collect();
+        let proof = SumcheckProof::new(Fr::ONE, messages.clone());
+
+        assert_eq!(proof.rounds(), &messages[..]);
+        for (round, message) in proof.rounds().iter().enumerate() {
+            assert_eq!(proof.get_round_message(round), Some(message));
+        }
+        assert_eq!(proof.get_round_message(3), None);
+    }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut rng = test_rng();
+        let messages: Vec<UnivariateEvals<Fr>> = (0..4)
+            .map(|_| UnivariateEvals::random(2, &mut rng))
+            .collect();
+        let proof = SumcheckProof::new(Fr::ONE, messages.clone());
+
+        let collected: Vec<&UnivariateEvals<Fr>> = (&proof).into_iter().collect();
+        assert_eq!(collected.len(), proof.num_rounds());
+        for (round, msg) in (&proof).into_iter().enumerate() {
+            assert_eq!(msg, &messages[round]);
+        }
+    }
+
+    #[test]
+    fn test_metadata_ignored_by_equality() {
+        let mut rng = test_rng();
+        let messages: Vec<UnivariateEvals<Fr>> = (0..2)
+            .map(|_| UnivariateEvals::random(2, &mut rng))
+            .collect();
+        let plain = SumcheckProof::new(Fr::ONE, messages.clone());
+        let labeled = SumcheckProof::new(Fr::ONE, messages.clone())
+            .with_metadata("layer 3 of a depth-5 GKR circuit, 4 variables");
+        let relabeled = SumcheckProof::new(Fr::ONE, messages).with_metadata("layer 4");
+
+        assert_eq!(plain, labeled);
+        assert_eq!(labeled, relabeled);
+        assert_eq!(plain.get_metadata(), None);
+        assert_eq!(
+            labeled.get_metadata(),
+            Some("layer 3 of a depth-5 GKR circuit, 4 variables")
+        );
+        assert!(format!("{labeled:?}").contains("layer 3 of a depth-5 GKR circuit"));
+    }
+
+    #[test]
+    fn test_evaluate_at_zero_and_one() {
+        let mut rng = test_rng();
+        for degree in 0..4 {
+            let poly = UnivariateEvals::<Fr>::random(degree, &mut rng);
+            assert_eq!(
+                poly.evaluate_at_zero(),
+                poly.evaluate_at_a_point(Fr::ZERO).unwrap()
+            );
+            assert_eq!(
+                poly.evaluate_at_one(),
+                poly.evaluate_at_a_point(Fr::ONE).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_with_sum_zero() {
+        let mut rng = test_rng();
+        for degree in 1..5 {
+            let poly = UnivariateEvals::<Fr>::random_with_sum_zero(degree, &mut rng);
+            let evals = poly.get_raw_evals();
+            assert_eq!(poly.get_degree(), degree);
+            assert_eq!(evals[0] + evals[1], Fr::ZERO);
+        }
+        assert_eq!(UnivariateEvals::<Fr>::random(3, &mut rng).get_degree(), 3);
+    }
+
+    #[test]
+    fn test_trim_to_degree() {
+        // 2 + 5x, stored as a degree-3 polynomial.
+        let evals: Vec<Fr> = [2, 7, 12, 17].into_iter().map(Fr::from).collect();
+        let trimmed = UnivariateEvals::new(evals.clone()).trim_to_degree();
+        assert_eq!(trimmed.get_degree(), 1);
+        assert_eq!(trimmed.get_raw_evals(), evals[..2].to_vec());
+
+        // x^3 + 1 cannot be trimmed.
+        let evals: Vec<Fr> = [1, 2, 9, 28].into_iter().map(Fr::from).collect();
+        assert_eq!(UnivariateEvals::new(evals).trim_to_degree().get_degree(), 3);
+
+        let zero = UnivariateEvals::new(vec![Fr::from(0); 3]).trim_to_degree();
+        assert_eq!(zero.get_degree(), 0);
+    }
+}
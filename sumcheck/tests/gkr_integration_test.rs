@@ -0,0 +1,69 @@
+//! End-to-end GKR tests: build a circuit, evaluate it, prove its output and
+//! verify the proof.
+
+use ark_std::test_rng;
+use shared_types::{ff_field, transcript::poseidon_sponge::PoseidonSponge, Fr};
+use sumcheck::gkr::{
+    circuit::{random_circuit, GkrCircuit, GkrCircuitBuilder},
+    gkr_prove, gkr_verify,
+};
+
+/// Three layers of eight gates each: products of neighbouring inputs, then
+/// sums of products four apart, then products of neighbouring sums.
+fn multiply_then_add_circuit(input: Vec<Fr>) -> GkrCircuit<Fr> {
+    let wiring = |offset: usize| (0..8).map(|i| (i, (i + offset) % 8)).collect();
+    GkrCircuitBuilder::new()
+        .add_input_layer(input)
+        .add_mul_layer(wiring(1))
+        .add_add_layer(wiring(4))
+        .add_mul_layer(wiring(1))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_gkr_end_to_end() {
+    let input: Vec<Fr> = (1..=8).map(Fr::from).collect();
+    let circuit = multiply_then_add_circuit(input.clone());
+    assert_eq!(circuit.check_well_formed(), Ok(()));
+
+    let products: Vec<Fr> = (0..8).map(|i| input[i] * input[(i + 1) % 8]).collect();
+    let sums: Vec<Fr> = (0..8)
+        .map(|i| products[i] + products[(i + 4) % 8])
+        .collect();
+    let expected: Vec<Fr> = (0..8).map(|i| sums[i] * sums[(i + 1) % 8]).collect();
+    let output = circuit.evaluate();
+    assert_eq!(output, expected);
+
+    let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+    assert!(gkr_verify(
+        &mut PoseidonSponge::default(),
+        &circuit,
+        &output,
+        &proof
+    ));
+
+    let mut wrong_output = output;
+    wrong_output[5] += Fr::ONE;
+    assert!(!gkr_verify(
+        &mut PoseidonSponge::default(),
+        &circuit,
+        &wrong_output,
+        &proof
+    ));
+}
+
+#[test]
+fn test_gkr_end_to_end_random_circuit() {
+    let circuit = random_circuit::<Fr>(4, 4, &mut test_rng());
+    assert_eq!(circuit.check_well_formed(), Ok(()));
+
+    let output = circuit.evaluate();
+    let proof = gkr_prove(&mut PoseidonSponge::default(), &circuit).unwrap();
+    assert!(gkr_verify(
+        &mut PoseidonSponge::default(),
+        &circuit,
+        &output,
+        &proof
+    ));
+}
@@ -0,0 +1,103 @@
+//! Runs the sumcheck prover and verifier side by side, checking every
+//! invariant of the protocol round by round so that a failure points at the
+//! exact round and check which broke.
+
+use ark_std::{rand::Rng, test_rng};
+use shared_types::{
+    transcript::{poseidon_sponge::PoseidonSponge, TranscriptSponge},
+    Field, Fr,
+};
+use sumcheck::{mle::MultilinearExtension, sumcheck::sumcheck_prove_with_challenges};
+
+/// The invariants checked by the [SumcheckSimulator].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Invariant {
+    /// g_i(0) + g_i(1) equals the previous round's claim.
+    RoundSum,
+    /// The degree of g_i equals the number of factors which still depend on
+    /// the i-th variable.
+    Degree,
+    /// The prover's challenge r_i is the one squeezed from the verifier's
+    /// transcript.
+    Challenge,
+    /// g_n(r_n) equals the product of the factors evaluated at (r_1, ..., r_n).
+    FinalEvaluation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimulationResult {
+    Success,
+    Failure { round: usize, invariant: Invariant },
+}
+
+struct SumcheckSimulator<F: Field> {
+    mles: Vec<MultilinearExtension<F>>,
+}
+
+impl<F: Field> SumcheckSimulator<F> {
+    fn new(mles: Vec<MultilinearExtension<F>>) -> Self {
+        Self { mles }
+    }
+
+    fn run(&self) -> SimulationResult {
+        let (proof, challenges) =
+            sumcheck_prove_with_challenges(&mut PoseidonSponge::default(), &self.mles);
+        let round_messages = proof.rounds();
+        let num_rounds = round_messages.len();
+
+        let mut verifier_transcript = PoseidonSponge::default();
+        verifier_transcript.absorb(proof.get_claimed_sum());
+        let mut claim = proof.get_claimed_sum();
+
+        for (round, message) in round_messages.iter().enumerate() {
+            let failure = |invariant| SimulationResult::Failure { round, invariant };
+
+            if !message.is_consistent_with_partial_sum(claim) {
+                return failure(Invariant::RoundSum);
+            }
+            let num_active = self.mles.iter().filter(|f| f.num_vars() > round).count();
+            if message.get_degree() != num_active {
+                return failure(Invariant::Degree);
+            }
+
+            verifier_transcript.absorb_elements(&message.get_raw_evals());
+            let r_i = verifier_transcript.squeeze();
+            if challenges.get(round) != Some(&r_i) {
+                return failure(Invariant::Challenge);
+            }
+            claim = message.evaluate_at_a_point(r_i).unwrap();
+        }
+
+        let oracle_query = self.mles.iter().fold(F::ONE, |acc, f| {
+            acc * f.evaluate(&challenges[..f.num_vars()])
+        });
+        if oracle_query != claim {
+            return SimulationResult::Failure {
+                round: num_rounds,
+                invariant: Invariant::FinalEvaluation,
+            };
+        }
+        SimulationResult::Success
+    }
+}
+
+#[test]
+fn test_simulator_on_random_instances() {
+    let mut rng = test_rng();
+    for _ in 0..100 {
+        let num_mles = rng.gen_range(1..=3);
+        let mles = (0..num_mles)
+            .map(|_| {
+                let num_vars = rng.gen_range(1..=4);
+                let table = (0..(1 << num_vars))
+                    .map(|_| Fr::from(rng.gen::<u64>()))
+                    .collect();
+                MultilinearExtension::new(table)
+            })
+            .collect();
+        assert_eq!(
+            SumcheckSimulator::<Fr>::new(mles).run(),
+            SimulationResult::Success
+        );
+    }
+}